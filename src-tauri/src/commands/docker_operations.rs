@@ -40,6 +40,8 @@ pub async fn scan_docker_containers() -> Result<Vec<ScanResult>, String> {
                             size,
                             file_type: "docker_container".to_string(),
                             can_delete: true,
+                            owner_tool: "Docker".to_string(),
+                            ..Default::default()
                         });
                     }
                 }
@@ -85,6 +87,8 @@ pub async fn scan_docker_images() -> Result<Vec<ScanResult>, String> {
                             size,
                             file_type: "docker_image".to_string(),
                             can_delete: true,
+                            owner_tool: "Docker".to_string(),
+                            ..Default::default()
                         });
                     }
                 }
@@ -146,6 +150,8 @@ pub async fn scan_docker_volumes() -> Result<Vec<ScanResult>, String> {
                         size,
                         file_type: "docker_volume".to_string(),
                         can_delete: true,
+                        owner_tool: "Docker".to_string(),
+                        ..Default::default()
                     });
                 }
             }
@@ -195,6 +201,8 @@ pub async fn scan_docker_cache() -> Result<Vec<ScanResult>, String> {
                                 size,
                                 file_type: "docker_cache".to_string(),
                                 can_delete: true,
+                                owner_tool: "Docker".to_string(),
+                                ..Default::default()
                             });
                         }
                     }
@@ -270,5 +278,6 @@ pub async fn clean_docker_resources(resource_paths: Vec<String>) -> Result<Clean
         space_freed,
         duration,
         errors,
+        ..Default::default()
     })
 }
@@ -1,73 +1,490 @@
 use crate::models::{CleaningResult, ScanResult};
-use crate::utils::get_dir_size;
-use std::path::PathBuf;
+use crate::progress::CancelToken;
+use crate::projects::workspace_config;
+use crate::registry::{
+    avd_dir, avd_home, daemons_holding, is_avd_dir, running_avds, running_daemons, stop_daemon,
+};
+use crate::utils::{get_dir_size_observed, save_size_index, size_index, NoProgress, SizeObserver};
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 #[tauri::command]
 pub async fn scan_node_modules(project_path: String) -> Result<Vec<ScanResult>, String> {
+    let results = tokio::task::spawn_blocking(move || {
+        let skip_dirs = workspace_config().skip_dirs;
+        find_node_modules(Path::new(&project_path), &skip_dirs, &NoProgress, |_| {})
+    })
+    .await
+    .map_err(|e| format!("Scan task failed: {e}"))?;
+    let _ = save_size_index();
+
+    results
+}
+
+/// Sizes every outermost `node_modules` under `path`, handing each to
+/// `on_found` as soon as it is measured. Nested ones are already part of
+/// their parent's size, so the results never overlap. Folders named in
+/// `skip_dirs` are not entered.
+pub fn find_node_modules(
+    path: &Path,
+    skip_dirs: &[String],
+    observer: &dyn SizeObserver,
+    mut on_found: impl FnMut(&ScanResult),
+) -> Result<Vec<ScanResult>, String> {
     let mut results = Vec::new();
-    let path = PathBuf::from(project_path);
+    walk_node_modules(path, skip_dirs, observer, |dir| {
+        let result = size_node_modules(dir, observer);
+        on_found(&result);
+        results.push(result);
+    })?;
+
+    Ok(results)
+}
 
+/// Hands every outermost `node_modules` under `path` to `on_dir` without
+/// sizing it, for callers that measure the folders another way.
+pub fn walk_node_modules(
+    path: &Path,
+    skip_dirs: &[String],
+    observer: &dyn SizeObserver,
+    mut on_dir: impl FnMut(&Path),
+) -> Result<(), String> {
     if !path.exists() {
         return Err("Project path does not exist".to_string());
     }
 
-    for entry in WalkDir::new(&path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_name() == "node_modules" && e.file_type().is_dir())
-    {
-        if let Ok(size) = get_dir_size(entry.path()) {
-            results.push(ScanResult {
-                path: entry.path().to_string_lossy().to_string(),
-                size,
-                file_type: "node_modules".to_string(),
-                can_delete: true,
-            });
+    let mut walker = WalkDir::new(path).into_iter().filter_entry(|entry| {
+        entry.depth() == 0
+            || !entry.file_type().is_dir()
+            || !skip_dirs
+                .iter()
+                .any(|skip| entry.file_name() == skip.as_str())
+    });
+
+    while let Some(entry) = walker.next() {
+        if observer.is_cancelled() {
+            break;
+        }
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if entry.file_name() != "node_modules" || !entry.file_type().is_dir() {
+            continue;
         }
+        walker.skip_current_dir();
+
+        on_dir(entry.path());
     }
 
-    Ok(results)
+    Ok(())
+}
+
+pub fn size_node_modules(dir: &Path, observer: &dyn SizeObserver) -> ScanResult {
+    let size = get_dir_size_observed(dir, observer);
+    ScanResult::from_dir_size(dir, "node_modules", "npm / Yarn", size)
 }
 
 #[tauri::command]
-pub async fn clean_files(file_paths: Vec<String>) -> Result<CleaningResult, String> {
+pub async fn clean_files(
+    file_paths: Vec<String>,
+    stop_daemons: Option<bool>,
+) -> Result<CleaningResult, String> {
+    tokio::task::spawn_blocking(move || {
+        clean_paths(
+            file_paths,
+            stop_daemons.unwrap_or(false),
+            &CancelToken::default(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Clean task failed: {e}"))
+}
+
+/// Deletes the AVD `id`: its folder and the `<id>.ini` the emulator lists
+/// it by. Refused while an emulator runs it.
+#[tauri::command]
+pub async fn delete_android_virtual_device(id: String) -> Result<CleaningResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let home = avd_home().ok_or("No AVD home found")?;
+        let ini_path = home.join(format!("{id}.ini"));
+        if id.contains(['/', '\\']) || !ini_path.is_file() {
+            return Err(format!("Unknown AVD: {id}"));
+        }
+        if running_avds().contains(&id) {
+            return Err(format!("{id} is running in the emulator"));
+        }
+
+        let cancel = CancelToken::default();
+        let mut result = match avd_dir(&ini_path, &home) {
+            Some(dir) if is_avd_dir(&dir) => {
+                delete_paths(vec![dir.to_string_lossy().to_string()], &cancel)
+            }
+            Some(dir) => {
+                return Err(format!(
+                    "{} does not look like an AVD folder",
+                    dir.display()
+                ))
+            }
+            None => CleaningResult::default(),
+        };
+
+        // Kept when the folder could not be removed, so the AVD stays listed.
+        if result.errors.is_empty() {
+            let pointer = delete_paths(vec![ini_path.to_string_lossy().to_string()], &cancel);
+            result.files_deleted += pointer.files_deleted;
+            result.space_freed += pointer.space_freed;
+            result.duration += pointer.duration;
+            result.errors = pointer.errors;
+            result.deleted_paths.extend(pointer.deleted_paths);
+        }
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("AVD deletion failed: {e}"))?
+}
+
+/// Deletes `file_paths`, first stopping the daemons that hold them when
+/// `stop_daemons` is set. Daemons that refuse to stop are reported as errors
+/// and their files deleted anyway.
+pub fn clean_paths(
+    file_paths: Vec<String>,
+    stop_daemons: bool,
+    cancel: &CancelToken,
+) -> CleaningResult {
+    let mut stopped_daemons = Vec::new();
+    let mut errors = Vec::new();
+
+    if stop_daemons {
+        for daemon in daemons_holding(&file_paths, running_daemons()) {
+            match stop_daemon(&daemon) {
+                Ok(()) => stopped_daemons.push(daemon.name),
+                Err(e) => errors.push(e),
+            }
+        }
+        // Several Gradle daemons share one name.
+        stopped_daemons.sort();
+        stopped_daemons.dedup();
+    }
+
+    let mut result = delete_paths(file_paths, cancel);
+    result.stopped_daemons = stopped_daemons;
+    errors.append(&mut result.errors);
+    result.errors = errors;
+    result
+}
+
+/// Deletes `file_paths` in order, stopping between entries once `cancel` is
+/// set. The result lists what was removed and is marked partial if paths
+/// were left untouched or only partly removed.
+pub fn delete_paths(file_paths: Vec<String>, cancel: &CancelToken) -> CleaningResult {
     let start_time = std::time::Instant::now();
     let mut files_deleted = 0;
     let mut space_freed = 0;
     let mut errors = Vec::new();
+    let mut deleted_paths = Vec::new();
+    let mut attempted = Vec::new();
+    let mut partial = false;
 
     for file_path in file_paths {
+        if cancel.is_cancelled() {
+            partial = true;
+            break;
+        }
+
         let path = PathBuf::from(&file_path);
+        attempted.push(path.clone());
 
-        if path.exists() {
-            if let Ok(size) = if path.is_dir() {
-                get_dir_size(&path)
-            } else {
-                path.metadata().map(|m| m.len())
-            } {
-                space_freed += size;
-            }
+        // symlink_metadata so a link is removed itself, never its target.
+        let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+            continue;
+        };
+        let mut removal = Removal::default();
+        if metadata.file_type().is_symlink() {
+            removal.record(&path, remove_symlink(&path).map(|_| 0));
+        } else if metadata.is_dir() {
+            remove_tree(&path, cancel, &mut removal);
+        } else {
+            removal.record(&path, remove_file(&path));
+        }
 
-            let result = if path.is_dir() {
-                std::fs::remove_dir_all(&path)
-            } else {
-                std::fs::remove_file(&path)
-            };
+        space_freed += removal.freed;
+        partial |= removal.cancelled || (!removal.errors.is_empty() && removal.removed > 0);
+        if let Some(error) = removal.error_summary(&file_path) {
+            errors.push(error);
+        } else if !removal.cancelled {
+            files_deleted += 1;
+            deleted_paths.push(file_path);
+        }
+    }
 
-            match result {
-                Ok(_) => files_deleted += 1,
-                Err(e) => errors.push(format!("Failed to delete {file_path}: {e}")),
-            }
+    // Failed and cancelled deletes may still have removed part of a tree.
+    if let Some(index) = size_index() {
+        for path in &attempted {
+            index.forget(path);
         }
+        let _ = index.save();
     }
 
     let duration = start_time.elapsed().as_millis() as u64;
 
-    Ok(CleaningResult {
+    CleaningResult {
         files_deleted,
         space_freed,
         duration,
         errors,
-    })
+        deleted_paths,
+        partial,
+        stopped_daemons: Vec::new(),
+    }
+}
+
+/// What deleting one of the requested paths did.
+#[derive(Default)]
+struct Removal {
+    /// Disk space released, counted as entries go.
+    freed: u64,
+    removed: u64,
+    errors: Vec<(PathBuf, std::io::Error)>,
+    cancelled: bool,
+}
+
+impl Removal {
+    fn record(&mut self, path: &Path, result: std::io::Result<u64>) {
+        match result {
+            Ok(freed) => {
+                self.freed += freed;
+                self.removed += 1;
+            }
+            Err(e) => self.errors.push((path.to_path_buf(), e)),
+        }
+    }
+
+    fn error_summary(&self, file_path: &str) -> Option<String> {
+        let (path, error) = self.errors.first()?;
+        Some(match self.errors.len() {
+            1 if path == Path::new(file_path) => format!("Failed to delete {file_path}: {error}"),
+            count => format!(
+                "Failed to delete {count} entries in {file_path}, such as {}: {error}",
+                path.display()
+            ),
+        })
+    }
+}
+
+/// Removes a directory tree bottom-up, checking `cancel` between entries.
+/// Entries that cannot be removed are recorded and skipped, along with the
+/// folders holding them, so the rest of the tree still goes.
+fn remove_tree(path: &Path, cancel: &CancelToken, removal: &mut Removal) {
+    for entry in WalkDir::new(path).contents_first(true) {
+        if cancel.is_cancelled() {
+            removal.cancelled = true;
+            return;
+        }
+
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let failed = e.path().unwrap_or(path).to_path_buf();
+                removal.errors.push((failed, e.into()));
+                continue;
+            }
+        };
+
+        let entry_path = entry.path();
+        let result = if entry.file_type().is_dir() {
+            let holds_failures = removal
+                .errors
+                .iter()
+                .any(|(failed, _)| failed.starts_with(entry_path));
+            if holds_failures {
+                continue;
+            }
+            std::fs::remove_dir(entry_path).map(|_| 0)
+        } else if entry.file_type().is_symlink() {
+            remove_symlink(entry_path).map(|_| 0)
+        } else {
+            remove_file(entry_path)
+        };
+        removal.record(entry_path, result);
+    }
+}
+
+/// Unlinks a file, returning the disk space that frees: none while other
+/// hard links still hold the data.
+fn remove_file(path: &Path) -> std::io::Result<u64> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    std::fs::remove_file(path)?;
+    Ok(freed_bytes(&metadata))
+}
+
+#[cfg(unix)]
+fn freed_bytes(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    if metadata.nlink() > 1 {
+        0
+    } else {
+        metadata.blocks() * 512
+    }
+}
+
+#[cfg(not(unix))]
+fn freed_bytes(metadata: &std::fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Windows directory symlinks and junctions need `remove_dir`; everything
+/// else is unlinked with `remove_file`. Neither touches the link target.
+fn remove_symlink(path: &Path) -> std::io::Result<()> {
+    std::fs::remove_file(path).or_else(|_| std::fs::remove_dir(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_files(dir: &tempfile::TempDir, count: usize) -> Vec<String> {
+        (0..count)
+            .map(|i| {
+                let file = dir.path().join(format!("{i}.tmp"));
+                std::fs::write(&file, "cache").unwrap();
+                file.to_string_lossy().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finished_deletes_are_not_partial() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = temp_files(&dir, 2);
+        let result = delete_paths(files.clone(), &CancelToken::default());
+
+        assert_eq!(result.deleted_paths, files);
+        assert!(!result.partial);
+    }
+
+    #[test]
+    fn skipped_paths_make_a_delete_partial() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = temp_files(&dir, 2);
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        let result = delete_paths(files.clone(), &cancel);
+
+        assert!(result.deleted_paths.is_empty());
+        assert!(result.partial);
+        assert!(files.iter().all(|file| Path::new(file).exists()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn freed_space_is_counted_while_deleting() {
+        let dir = tempfile::tempdir().unwrap();
+        let tree = dir.path().join("cache");
+        std::fs::create_dir_all(tree.join("nested")).unwrap();
+        std::fs::write(tree.join("a.bin"), vec![1; 8192]).unwrap();
+        std::fs::write(tree.join("nested/b.bin"), vec![1; 8192]).unwrap();
+        std::fs::write(tree.join("nested/shared.bin"), vec![1; 8192]).unwrap();
+        std::fs::hard_link(
+            tree.join("nested/shared.bin"),
+            dir.path().join("shared.bin"),
+        )
+        .unwrap();
+        let expected = get_dir_size_observed(&tree, &NoProgress).reclaimable_bytes();
+
+        let result = delete_paths(
+            vec![tree.to_string_lossy().to_string()],
+            &CancelToken::default(),
+        );
+
+        assert!(!tree.exists() && result.errors.is_empty());
+        assert_eq!(result.space_freed, expected);
+        assert!(dir.path().join("shared.bin").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn undeletable_entries_do_not_stop_the_rest_of_the_tree() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let tree = dir.path().join("cache");
+        let locked = tree.join("locked");
+        std::fs::create_dir_all(&locked).unwrap();
+        std::fs::write(locked.join("kept.bin"), vec![1; 4096]).unwrap();
+        std::fs::write(tree.join("gone.bin"), vec![1; 4096]).unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555)).unwrap();
+        // Root ignores the permissions, so there is nothing to fail.
+        if std::fs::write(locked.join("probe"), "").is_ok() {
+            return;
+        }
+
+        let result = delete_paths(
+            vec![tree.to_string_lossy().to_string()],
+            &CancelToken::default(),
+        );
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(!tree.join("gone.bin").exists());
+        assert!(locked.join("kept.bin").exists());
+        assert!(result.space_freed >= 4096);
+        assert_eq!(result.errors.len(), 1);
+        assert!(result.partial && result.deleted_paths.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn deleting_removes_links_and_never_their_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let tree = dir.path().join("cache");
+        let target = dir.path().join("target");
+        std::fs::create_dir_all(&tree).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(target.join("kept.bin"), "data").unwrap();
+        std::os::unix::fs::symlink(&target, tree.join("link")).unwrap();
+        let top_link = dir.path().join("top-link");
+        std::os::unix::fs::symlink(&target, &top_link).unwrap();
+
+        let result = delete_paths(
+            vec![
+                tree.to_string_lossy().to_string(),
+                top_link.to_string_lossy().to_string(),
+            ],
+            &CancelToken::default(),
+        );
+
+        assert!(result.errors.is_empty());
+        assert!(!tree.exists() && std::fs::symlink_metadata(&top_link).is_err());
+        assert!(target.join("kept.bin").is_file());
+    }
+
+    #[test]
+    fn only_the_outermost_node_modules_are_walked_and_skipped_dirs_are_not() {
+        let dir = tempfile::tempdir().unwrap();
+        for nested in [
+            "app/node_modules/dep/node_modules/inner",
+            "packages/lib/node_modules/dep",
+            "vendor/node_modules/dep",
+        ] {
+            std::fs::create_dir_all(dir.path().join(nested)).unwrap();
+        }
+
+        let mut found = Vec::new();
+        walk_node_modules(
+            dir.path(),
+            &["vendor".to_string()],
+            &NoProgress,
+            |node_modules| found.push(node_modules.to_path_buf()),
+        )
+        .unwrap();
+        found.sort();
+
+        assert_eq!(
+            found,
+            [
+                dir.path().join("app/node_modules"),
+                dir.path().join("packages/lib/node_modules"),
+            ]
+        );
+    }
 }
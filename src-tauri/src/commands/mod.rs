@@ -1,9 +1,13 @@
 pub mod cache_scanners;
 pub mod docker_operations;
 pub mod file_operations;
+pub mod project_scans;
+pub mod scan_sessions;
 pub mod system_info;
 
 pub use cache_scanners::*;
 pub use docker_operations::*;
 pub use file_operations::*;
+pub use project_scans::*;
+pub use scan_sessions::*;
 pub use system_info::*;
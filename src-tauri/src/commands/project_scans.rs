@@ -0,0 +1,157 @@
+use crate::commands::{size_node_modules, walk_node_modules};
+use crate::models::{ProjectActivity, ProjectStaleness, RnProject, ScanResult, WorkspaceConfig};
+use crate::progress::ScanReporter;
+use crate::projects::{
+    analyze_duplication, discover_projects, inventory_project, projects_activity,
+    save_workspace_config, workspace_config, workspace_roots,
+};
+use crate::utils::{save_size_index, scan_pool, unix_secs, SizeObserver};
+use rayon::prelude::*;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+pub const PROJECTS_FINISHED_EVENT: &str = "projects://finished";
+pub const STALE_PROJECTS_FINISHED_EVENT: &str = "projects://stale-finished";
+pub const DUPLICATION_FINISHED_EVENT: &str = "projects://duplication-finished";
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[tauri::command]
+pub async fn get_workspace_config() -> WorkspaceConfig {
+    workspace_config()
+}
+
+#[tauri::command]
+pub async fn set_workspace_config(config: WorkspaceConfig) -> Result<(), String> {
+    save_workspace_config(config)
+}
+
+/// Starts finding the React Native projects under the configured workspace
+/// roots and sizing the regenerable folders of each. Returns the scan id its
+/// `scan://progress` and `projects://finished` events will carry.
+#[tauri::command]
+pub async fn start_project_scan(app: AppHandle) -> Result<String, String> {
+    let reporter = ScanReporter::new(app, None);
+    let scan_id = reporter.scan_id().to_string();
+
+    std::thread::spawn(move || {
+        let roots = workspace_roots(&workspace_config());
+        let projects: Vec<RnProject> = scan_pool().install(|| {
+            discover_projects(&roots, &reporter)
+                .par_iter()
+                .filter(|_| !reporter.is_cancelled())
+                .filter_map(|path| inventory_project(path, &reporter))
+                .collect()
+        });
+
+        let _ = save_size_index();
+        reporter.finished_with(PROJECTS_FINISHED_EVENT, Ok(projects));
+    });
+
+    Ok(scan_id)
+}
+
+/// Every outermost `node_modules` under the workspace roots, unsized.
+fn workspace_node_modules(
+    config: &WorkspaceConfig,
+    observer: &dyn SizeObserver,
+) -> Result<Vec<PathBuf>, String> {
+    let mut node_modules = Vec::new();
+    for root in workspace_roots(config).iter().filter(|root| root.is_dir()) {
+        walk_node_modules(root, &config.skip_dirs, observer, |dir| {
+            node_modules.push(dir.to_path_buf())
+        })?;
+    }
+
+    // Overlapping roots find the same folders twice.
+    node_modules.sort();
+    node_modules.dedup();
+    Ok(node_modules)
+}
+
+/// Starts pairing every outermost `node_modules` under the workspace roots
+/// with when its project was last worked on, flagging large ones left
+/// untouched for longer than the configured period. Returns the scan id its
+/// `scan://` and `projects://stale-finished` events will carry.
+#[tauri::command]
+pub async fn start_stale_projects_scan(app: AppHandle) -> Result<String, String> {
+    let reporter = ScanReporter::new(app, None);
+    let scan_id = reporter.scan_id().to_string();
+
+    std::thread::spawn(move || {
+        let config = workspace_config();
+        let outcome = workspace_node_modules(&config, &reporter).map(|dirs| {
+            scan_pool().install(|| {
+                // One project per outermost `node_modules`.
+                let (projects, sized): (Vec<PathBuf>, Vec<ScanResult>) = dirs
+                    .par_iter()
+                    .filter(|_| !reporter.is_cancelled())
+                    .filter_map(|dir| {
+                        let project = dir.parent()?.to_path_buf();
+                        let node_modules = size_node_modules(dir, &reporter);
+                        reporter.item_found(&node_modules);
+                        Some((project, node_modules))
+                    })
+                    .unzip();
+
+                let activities = projects_activity(&projects, &config.skip_dirs);
+                projects
+                    .into_iter()
+                    .zip(sized)
+                    .zip(activities)
+                    .map(|((project, node_modules), activity)| {
+                        staleness(node_modules, project, activity, &config)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        });
+
+        let _ = save_size_index();
+        reporter.finished_with(STALE_PROJECTS_FINISHED_EVENT, outcome);
+    });
+
+    Ok(scan_id)
+}
+
+fn staleness(
+    node_modules: ScanResult,
+    project: PathBuf,
+    activity: ProjectActivity,
+    config: &WorkspaceConfig,
+) -> ProjectStaleness {
+    let now = unix_secs(Ok(SystemTime::now())).unwrap_or_default();
+    let inactive_days = activity
+        .last_active_at
+        .map(|active| now.saturating_sub(active) / SECONDS_PER_DAY);
+    let stale = inactive_days.is_some_and(|days| days >= config.stale_after_days)
+        && node_modules.size >= config.stale_min_bytes;
+
+    ProjectStaleness {
+        project_path: project.to_string_lossy().to_string(),
+        node_modules,
+        activity,
+        inactive_days,
+        stale,
+    }
+}
+
+/// Starts finding the packages installed at the same version in several
+/// projects' `node_modules` and what keeping one copy of each would save.
+/// Returns the scan id its `scan://progress` and
+/// `projects://duplication-finished` events will carry.
+#[tauri::command]
+pub async fn start_duplication_analysis(app: AppHandle) -> Result<String, String> {
+    let reporter = ScanReporter::new(app, None);
+    let scan_id = reporter.scan_id().to_string();
+
+    std::thread::spawn(move || {
+        let outcome = workspace_node_modules(&workspace_config(), &reporter)
+            .map(|dirs| scan_pool().install(|| analyze_duplication(&dirs, &reporter)));
+
+        let _ = save_size_index();
+        reporter.finished_with(DUPLICATION_FINISHED_EVENT, outcome);
+    });
+
+    Ok(scan_id)
+}
@@ -0,0 +1,146 @@
+use crate::commands::{clean_paths, find_node_modules};
+use crate::models::{CleanFinished, ScanResult};
+use crate::progress::{cancel_running_operation, finish_operation, start_operation, ScanReporter};
+use crate::projects::workspace_config;
+use crate::registry::{
+    find_target, mark_running_owners, running_daemons, CacheTarget, ScanContext, CACHE_TARGETS,
+};
+use crate::utils::{save_size_index, scan_pool};
+use crate::watcher::refresh_cache_watcher;
+use rayon::prelude::*;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter};
+
+pub const CLEAN_FINISHED_EVENT: &str = "clean://finished";
+
+/// Starts scanning `categories` (all of them when omitted) in the background
+/// and returns the scan id its `scan://` events will carry.
+#[tauri::command]
+pub async fn start_scan(app: AppHandle, categories: Option<Vec<String>>) -> Result<String, String> {
+    let targets: Vec<&'static dyn CacheTarget> = match categories {
+        Some(ids) => ids
+            .iter()
+            .map(|id| find_target(id).ok_or_else(|| format!("Unknown cache category: {id}")))
+            .collect::<Result<_, _>>()?,
+        None => CACHE_TARGETS.to_vec(),
+    };
+
+    let reporter = ScanReporter::new(app, Some(targets.len()));
+    let scan_id = reporter.scan_id().to_string();
+
+    std::thread::spawn(move || {
+        let outcome = scan_targets(&targets, &reporter);
+        let _ = save_size_index();
+        refresh_cache_watcher();
+        reporter.finished(&outcome);
+    });
+
+    Ok(scan_id)
+}
+
+/// Runs a scan like [`start_scan`] but resolves with its results. It is
+/// still a scan operation: its `scan://` events carry the id that
+/// [`cancel_operation`] stops it with.
+pub async fn scan_to_completion(
+    app: AppHandle,
+    targets: Vec<&'static dyn CacheTarget>,
+) -> Result<Vec<ScanResult>, String> {
+    tokio::task::spawn_blocking(move || {
+        let reporter = ScanReporter::new(app, Some(targets.len()));
+        let outcome = scan_targets(&targets, &reporter);
+        let _ = save_size_index();
+        refresh_cache_watcher();
+        reporter.finished(&outcome);
+        outcome
+    })
+    .await
+    .map_err(|e| format!("Scan task failed: {e}"))?
+}
+
+fn scan_targets(
+    targets: &[&'static dyn CacheTarget],
+    reporter: &ScanReporter,
+) -> Result<Vec<ScanResult>, String> {
+    let daemons = running_daemons();
+    let context = ScanContext::new(reporter);
+    scan_pool()
+        .install(|| {
+            targets
+                .par_iter()
+                .map(|target| {
+                    if reporter.is_cancelled() {
+                        return Ok(Vec::new());
+                    }
+
+                    let mut results = target.scan(&context)?;
+                    mark_running_owners(&mut results, &daemons);
+                    for result in &results {
+                        reporter.item_found(result);
+                    }
+                    reporter.category_completed();
+                    Ok(results)
+                })
+                .collect::<Result<Vec<_>, String>>()
+        })
+        .map(|results| results.into_iter().flatten().collect())
+}
+
+#[tauri::command]
+pub async fn start_node_modules_scan(
+    app: AppHandle,
+    project_path: String,
+) -> Result<String, String> {
+    let reporter = ScanReporter::new(app, None);
+    let scan_id = reporter.scan_id().to_string();
+
+    std::thread::spawn(move || {
+        let path = PathBuf::from(project_path);
+        let skip_dirs = workspace_config().skip_dirs;
+        let outcome = find_node_modules(&path, &skip_dirs, &reporter, |result| {
+            reporter.item_found(result)
+        });
+
+        let _ = save_size_index();
+        reporter.finished(&outcome);
+    });
+
+    Ok(scan_id)
+}
+
+/// Starts deleting `file_paths` in the background and returns the operation
+/// id its `clean://finished` event will carry.
+#[tauri::command]
+pub async fn start_clean(
+    app: AppHandle,
+    file_paths: Vec<String>,
+    stop_daemons: Option<bool>,
+) -> Result<String, String> {
+    let (operation_id, cancel) = start_operation("clean");
+    let id = operation_id.clone();
+
+    std::thread::spawn(move || {
+        let result = clean_paths(file_paths, stop_daemons.unwrap_or(false), &cancel);
+        finish_operation(&operation_id);
+
+        let _ = app.emit(
+            CLEAN_FINISHED_EVENT,
+            CleanFinished {
+                operation_id,
+                result,
+            },
+        );
+    });
+
+    Ok(id)
+}
+
+/// Asks a running scan or clean to stop. Its finished event still fires,
+/// with the result marked partial.
+#[tauri::command]
+pub fn cancel_operation(id: String) -> Result<(), String> {
+    if cancel_running_operation(&id) {
+        Ok(())
+    } else {
+        Err(format!("No running operation with id {id}"))
+    }
+}
@@ -1,10 +1,18 @@
 mod commands;
 mod models;
+mod progress;
+mod projects;
+mod registry;
 mod tray;
 mod utils;
+mod watcher;
 
 use commands::*;
+use projects::{load_workspace_config, WORKSPACE_CONFIG_FILE};
+use tauri::Manager;
 use tray::create_tray;
+use utils::{load_size_index, SIZE_INDEX_FILE};
+use watcher::start_cache_watcher;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -15,41 +23,48 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
             greet,
-            scan_expo_cache,
-            scan_metro_cache,
-            scan_ios_cache,
-            scan_android_cache,
-            scan_npm_cache,
-            scan_watchman_cache,
-            scan_cocoapods_cache,
-            scan_flipper_logs,
-            scan_temp_files,
-            scan_react_native_cache,
-            scan_hermes_cache,
-            scan_vscode_cache,
-            scan_android_studio_cache,
-            scan_build_artifacts,
-            scan_homebrew_cache,
+            list_categories,
+            scan_category,
+            scan_all_categories,
+            get_cached_sizes,
+            list_running_daemons,
+            scan_android_virtual_devices,
+            delete_android_virtual_device,
+            set_scan_threads,
+            set_package_manager_queries,
             scan_docker_containers,
             scan_docker_images,
             scan_docker_volumes,
             scan_docker_cache,
             clean_docker_resources,
             scan_node_modules,
+            get_workspace_config,
+            set_workspace_config,
+            start_project_scan,
+            start_stale_projects_scan,
+            start_duplication_analysis,
+            start_scan,
+            start_node_modules_scan,
             clean_files,
-            get_system_info,
-            scan_git_cache,
-            scan_intellij_cache,
-            scan_python_cache,
-            scan_rust_cache,
-            scan_browser_cache,
-            scan_system_logs,
-            scan_pnpm_cache,
-            scan_unity_cache,
-            scan_simulator_cache
+            start_clean,
+            cancel_operation,
+            get_system_info
         ])
         .setup(|app| {
+            let data_dir = app.path().app_data_dir().ok();
+            if let Some(data_dir) = &data_dir {
+                load_workspace_config(data_dir.join(WORKSPACE_CONFIG_FILE));
+            }
             create_tray(app.handle())?;
+
+            // The watcher starts from the index, which can take a while to load.
+            let app = app.handle().clone();
+            std::thread::spawn(move || {
+                if let Some(data_dir) = data_dir {
+                    load_size_index(data_dir.join(SIZE_INDEX_FILE));
+                }
+                start_cache_watcher(app);
+            });
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -1,17 +1,326 @@
+use crate::utils::DirSize;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CleaningResult {
     pub files_deleted: u32,
     pub space_freed: u64,
     pub duration: u64,
     pub errors: Vec<String>,
+    #[serde(default)]
+    pub deleted_paths: Vec<String>,
+    #[serde(default)]
+    pub partial: bool,
+    /// Daemons stopped before deleting, by name.
+    #[serde(default)]
+    pub stopped_daemons: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ScanResult {
     pub path: String,
     pub size: u64,
     pub file_type: String,
     pub can_delete: bool,
+    #[serde(default)]
+    pub allocated_size: u64,
+    #[serde(default)]
+    pub linked_elsewhere_size: u64,
+    #[serde(default)]
+    pub linked_elsewhere_files: u64,
+    #[serde(default)]
+    pub unreadable_entries: u64,
+    #[serde(default)]
+    pub unreadable_sample: Vec<String>,
+    #[serde(default)]
+    pub external_symlinks: u64,
+    #[serde(default)]
+    pub external_symlink_sample: Vec<String>,
+    #[serde(default)]
+    pub partial: bool,
+    /// Unix seconds; `None` when the platform does not report them.
+    #[serde(default)]
+    pub modified_at: Option<u64>,
+    #[serde(default)]
+    pub accessed_at: Option<u64>,
+    #[serde(default)]
+    pub newest_file_at: Option<u64>,
+    #[serde(default)]
+    pub file_count: u64,
+    #[serde(default)]
+    pub item_count: u64,
+    /// The tool that creates and owns this data, e.g. "Gradle".
+    #[serde(default)]
+    pub owner_tool: String,
+    /// `"default"`, or the environment variable a category location came
+    /// from. Empty for results found some other way.
+    #[serde(default)]
+    pub path_source: String,
+    /// Which part of a category this is, for categories broken down further,
+    /// e.g. `gradle_wrapper_dist`.
+    #[serde(default)]
+    pub component: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// The build number alongside `version`, e.g. `21E219`.
+    #[serde(default)]
+    pub build: Option<String>,
+    /// Projects that still need this entry; such entries are not deletable.
+    #[serde(default)]
+    pub used_by: Vec<String>,
+    /// Running daemons that hold or recreate these files, by name.
+    #[serde(default)]
+    pub running_owners: Vec<String>,
+    /// The project or workspace this entry was created for.
+    #[serde(default)]
+    pub origin: Option<String>,
+    /// `origin` no longer exists on disk.
+    #[serde(default)]
+    pub origin_missing: bool,
+    /// When the owning tool last used this entry, as Unix seconds.
+    #[serde(default)]
+    pub last_used_at: Option<u64>,
+    /// Kept by a retention setting; such entries are not deletable.
+    #[serde(default)]
+    pub retained: bool,
+}
+
+impl ScanResult {
+    pub fn from_dir_size(path: &Path, file_type: &str, owner_tool: &str, size: DirSize) -> Self {
+        ScanResult {
+            path: path.to_string_lossy().to_string(),
+            size: size.bytes,
+            file_type: file_type.to_string(),
+            can_delete: true,
+            allocated_size: size.allocated_bytes,
+            linked_elsewhere_size: size.linked_elsewhere_bytes,
+            linked_elsewhere_files: size.linked_elsewhere_files,
+            unreadable_entries: size.unreadable_count,
+            unreadable_sample: size.unreadable_sample,
+            external_symlinks: size.external_symlink_count,
+            external_symlink_sample: size.external_symlink_sample,
+            partial: size.interrupted,
+            modified_at: size.modified_at,
+            accessed_at: size.accessed_at,
+            newest_file_at: size.newest_file_at,
+            file_count: size.file_count,
+            item_count: size.item_count,
+            owner_tool: owner_tool.to_string(),
+            path_source: String::new(),
+            component: String::new(),
+            version: None,
+            build: None,
+            used_by: Vec::new(),
+            running_owners: Vec::new(),
+            origin: None,
+            origin_missing: false,
+            last_used_at: None,
+            retained: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Safe,
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheCategory {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub risk: RiskLevel,
+    pub owner: String,
+    pub locations: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanProgress {
+    pub scan_id: String,
+    pub bytes_counted: u64,
+    pub current_path: Option<String>,
+    pub categories_completed: usize,
+    pub categories_total: Option<usize>,
+    pub percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanItemFound {
+    pub scan_id: String,
+    pub item: ScanResult,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanFinished {
+    pub scan_id: String,
+    pub results: Vec<ScanResult>,
+    pub partial: bool,
+    pub error: Option<String>,
+}
+
+/// Final event of a tracked operation whose result is not a list of scan
+/// results, such as a project scan.
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationFinished<T> {
+    pub scan_id: String,
+    pub result: Option<T>,
+    pub partial: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanFinished {
+    pub operation_id: String,
+    pub result: CleaningResult,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SizesChanged {
+    pub changed: Vec<ScanResult>,
+    pub total_reclaimable: u64,
+    /// Folders the OS could not watch, checked by polling every few minutes.
+    pub polled: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectKind {
+    ReactNative,
+    Expo,
+}
+
+/// A React Native or Expo project and the regenerable folders inside it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RnProject {
+    pub path: String,
+    pub name: String,
+    pub kind: ProjectKind,
+    /// The files that identified the project, e.g. `package.json`.
+    pub markers: Vec<String>,
+    pub artifacts: Vec<ScanResult>,
+    pub artifacts_size: u64,
+}
+
+/// Where project discovery looks, relative paths being taken from home, and
+/// how much per-project tool data to keep.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    pub roots: Vec<String>,
+    /// Folder names the `node_modules` search never enters.
+    pub skip_dirs: Vec<String>,
+    /// Projects untouched for this long are stale...
+    pub stale_after_days: u64,
+    /// ...when their `node_modules` is at least this large.
+    pub stale_min_bytes: u64,
+    /// DerivedData folders of this many most recently built workspaces are
+    /// kept.
+    pub derived_data_keep: usize,
+    /// DeviceSupport of this many latest OS major.minor versions is kept,
+    /// per platform.
+    pub device_support_keep: usize,
+    /// Ask npm, yarn, pnpm, pip and CocoaPods where their caches are.
+    pub tool_queries: bool,
+}
+
+impl Default for WorkspaceConfig {
+    fn default() -> Self {
+        let strings = |items: &[&str]| items.iter().map(|item| item.to_string()).collect();
+
+        WorkspaceConfig {
+            roots: strings(&["Projects", "Developer", "dev", "code", "src", "workspace"]),
+            skip_dirs: strings(&[".git", "Pods", "build", ".gradle", "DerivedData"]),
+            stale_after_days: 60,
+            stale_min_bytes: 100 * 1024 * 1024,
+            derived_data_keep: 3,
+            device_support_keep: 2,
+            tool_queries: true,
+        }
+    }
+}
+
+/// When a project was last worked on, as Unix seconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectActivity {
+    /// Newest mtime among source files, dependencies and build output aside.
+    pub newest_source_at: Option<u64>,
+    pub last_commit_at: Option<u64>,
+    /// The later of the two.
+    pub last_active_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectStaleness {
+    pub project_path: String,
+    pub node_modules: ScanResult,
+    pub activity: ProjectActivity,
+    pub inactive_days: Option<u64>,
+    pub stale: bool,
+}
+
+/// One `name@version` installed in more than one `node_modules`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicatedPackage {
+    pub name: String,
+    pub version: String,
+    pub copies: usize,
+    pub total_size: u64,
+    /// Space taken beyond a single copy.
+    pub wasted_size: u64,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DuplicationReport {
+    pub node_modules_scanned: usize,
+    pub packages_scanned: usize,
+    /// Sorted by `wasted_size`, largest first.
+    pub duplicates: Vec<DuplicatedPackage>,
+    pub total_size: u64,
+    /// What every package would take with one copy per version, as in a
+    /// content-addressed store such as pnpm's.
+    pub unique_size: u64,
+    pub store_savings: u64,
+}
+
+/// A development daemon found running, such as the Gradle daemon or Metro.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningDaemon {
+    pub id: String,
+    pub name: String,
+    pub pid: u32,
+    pub command: String,
+    /// Categories whose files it holds open or recreates.
+    pub categories: Vec<String>,
+}
+
+/// An Android emulator image from the AVD home.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AndroidVirtualDevice {
+    /// The `<id>.ini` file name, which the emulator's `-avd` flag takes.
+    pub id: String,
+    pub name: String,
+    /// Deleting the whole AVD removes this file along with `avd.path`.
+    pub ini_path: String,
+    pub api_level: Option<String>,
+    /// The system image directory, relative to the SDK root.
+    pub system_image: Option<String>,
+    pub device: Option<String>,
+    /// The configured data partition size in bytes.
+    pub disk_size: Option<u64>,
+    /// The `<id>.avd` folder.
+    pub avd: ScanResult,
+    pub snapshots: Vec<ScanResult>,
+    /// `userdata-qemu.img` and its overlays; deleting them wipes the device.
+    pub user_data: Vec<ScanResult>,
+    /// Unix seconds, from the newest file in the AVD.
+    pub last_used_at: Option<u64>,
+    /// An emulator process has the AVD open.
+    pub running: bool,
 }
@@ -0,0 +1,5 @@
+pub mod operations;
+pub mod scan_reporter;
+
+pub use operations::*;
+pub use scan_reporter::*;
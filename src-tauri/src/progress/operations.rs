@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static NEXT_OPERATION: AtomicU64 = AtomicU64::new(1);
+static RUNNING: OnceLock<Mutex<HashMap<String, CancelToken>>> = OnceLock::new();
+
+/// Shared flag a long-running operation polls to stop early.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+fn running() -> &'static Mutex<HashMap<String, CancelToken>> {
+    RUNNING.get_or_init(Mutex::default)
+}
+
+/// Registers a new cancellable operation and returns its id and token.
+pub fn start_operation(kind: &str) -> (String, CancelToken) {
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let id = format!(
+        "{kind}-{started}-{}",
+        NEXT_OPERATION.fetch_add(1, Ordering::Relaxed)
+    );
+    let token = CancelToken::default();
+
+    running()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id.clone(), token.clone());
+
+    (id, token)
+}
+
+pub fn finish_operation(id: &str) {
+    running()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(id);
+}
+
+/// Flags the operation as cancelled. Returns false when no such operation
+/// is running.
+pub fn cancel_running_operation(id: &str) -> bool {
+    match running().lock().unwrap_or_else(|e| e.into_inner()).get(id) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,149 @@
+use crate::models::{OperationFinished, ScanFinished, ScanItemFound, ScanProgress, ScanResult};
+use crate::progress::{finish_operation, start_operation, CancelToken};
+use crate::utils::SizeObserver;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+pub const SCAN_PROGRESS_EVENT: &str = "scan://progress";
+pub const SCAN_ITEM_EVENT: &str = "scan://item";
+pub const SCAN_FINISHED_EVENT: &str = "scan://finished";
+
+/// Minimum gap between two progress events of the same scan.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Streams the progress of one scan to the frontend. Every event carries the
+/// scan id so concurrent scans can be told apart; the same id cancels it.
+pub struct ScanReporter {
+    app: AppHandle,
+    scan_id: String,
+    cancel: CancelToken,
+    categories_total: Option<usize>,
+    categories_completed: AtomicUsize,
+    bytes_counted: AtomicU64,
+    last_progress: Mutex<Instant>,
+}
+
+impl ScanReporter {
+    pub fn new(app: AppHandle, categories_total: Option<usize>) -> Self {
+        let (scan_id, cancel) = start_operation("scan");
+        ScanReporter {
+            app,
+            scan_id,
+            cancel,
+            categories_total,
+            categories_completed: AtomicUsize::new(0),
+            bytes_counted: AtomicU64::new(0),
+            last_progress: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn scan_id(&self) -> &str {
+        &self.scan_id
+    }
+
+    pub fn item_found(&self, item: &ScanResult) {
+        let _ = self.app.emit(
+            SCAN_ITEM_EVENT,
+            ScanItemFound {
+                scan_id: self.scan_id.clone(),
+                item: item.clone(),
+            },
+        );
+    }
+
+    pub fn category_completed(&self) {
+        self.categories_completed.fetch_add(1, Ordering::Relaxed);
+        self.emit_progress(None);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Emits the final results, marked partial when the scan was cancelled.
+    pub fn finished(&self, outcome: &Result<Vec<ScanResult>, String>) {
+        finish_operation(&self.scan_id);
+
+        let (results, error) = match outcome {
+            Ok(results) => (results.clone(), None),
+            Err(e) => (Vec::new(), Some(e.clone())),
+        };
+        let _ = self.app.emit(
+            SCAN_FINISHED_EVENT,
+            ScanFinished {
+                scan_id: self.scan_id.clone(),
+                results,
+                partial: self.is_cancelled(),
+                error,
+            },
+        );
+    }
+
+    /// Emits `event` with the outcome of an operation that produces
+    /// something other than scan results, marked partial when cancelled.
+    pub fn finished_with<T: Serialize + Clone>(&self, event: &str, outcome: Result<T, String>) {
+        finish_operation(&self.scan_id);
+
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(e) => (None, Some(e)),
+        };
+        let _ = self.app.emit(
+            event,
+            OperationFinished {
+                scan_id: self.scan_id.clone(),
+                result,
+                partial: self.is_cancelled(),
+                error,
+            },
+        );
+    }
+
+    fn emit_progress(&self, current_path: Option<&Path>) {
+        let categories_completed = self.categories_completed.load(Ordering::Relaxed);
+        let percent = self
+            .categories_total
+            .filter(|total| *total > 0)
+            .map(|total| categories_completed as f64 * 100.0 / total as f64);
+
+        let _ = self.app.emit(
+            SCAN_PROGRESS_EVENT,
+            ScanProgress {
+                scan_id: self.scan_id.clone(),
+                bytes_counted: self.bytes_counted.load(Ordering::Relaxed),
+                current_path: current_path.map(|p| p.to_string_lossy().to_string()),
+                categories_completed,
+                categories_total: self.categories_total,
+                percent,
+            },
+        );
+    }
+}
+
+impl SizeObserver for ScanReporter {
+    fn entered_dir(&self, path: &Path) {
+        // Workers that lose the race simply skip this update.
+        let Ok(mut last_progress) = self.last_progress.try_lock() else {
+            return;
+        };
+        if last_progress.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        *last_progress = Instant::now();
+        drop(last_progress);
+
+        self.emit_progress(Some(path));
+    }
+
+    fn counted_bytes(&self, bytes: u64) {
+        self.bytes_counted.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+}
@@ -0,0 +1,119 @@
+use crate::models::ProjectActivity;
+use crate::projects::PROJECT_ARTIFACTS;
+use crate::utils::{run_tool, unix_secs};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// When each of `projects` last changed, from its source files and git
+/// history, in the same order.
+///
+/// Dependencies, build output, hidden folders and anything named in
+/// `skip_dirs` are ignored: installs and builds touch them without anyone
+/// working on the project. Every file is read once, so a project nested in
+/// another is walked on its own and its newest source counts for both.
+pub fn projects_activity(projects: &[PathBuf], skip_dirs: &[String]) -> Vec<ProjectActivity> {
+    let nested: HashSet<&Path> = projects.iter().map(PathBuf::as_path).collect();
+    let own_newest: Vec<Option<u64>> = projects
+        .par_iter()
+        .map(|project| newest_source(project, skip_dirs, &nested))
+        .collect();
+
+    projects
+        .par_iter()
+        .map(|project| {
+            let newest_source_at = projects
+                .iter()
+                .zip(&own_newest)
+                .filter(|(other, _)| other.starts_with(project))
+                .filter_map(|(_, newest)| *newest)
+                .max();
+            let last_commit_at = git_last_commit(project);
+
+            ProjectActivity {
+                newest_source_at,
+                last_commit_at,
+                last_active_at: newest_source_at.max(last_commit_at),
+            }
+        })
+        .collect()
+}
+
+/// Newest mtime among `project`'s source files, not descending into the
+/// `other_projects` below it.
+fn newest_source(
+    project: &Path,
+    skip_dirs: &[String],
+    other_projects: &HashSet<&Path>,
+) -> Option<u64> {
+    let is_output = |path: &Path| {
+        path.strip_prefix(project).is_ok_and(|relative| {
+            PROJECT_ARTIFACTS
+                .iter()
+                .any(|(artifact, _, _)| relative == Path::new(artifact))
+        })
+    };
+
+    WalkDir::new(project)
+        .into_iter()
+        .filter_entry(|entry| {
+            if entry.depth() == 0 || !entry.file_type().is_dir() {
+                return true;
+            }
+            let name = entry.file_name().to_string_lossy();
+            !name.starts_with('.')
+                && name != "node_modules"
+                && !skip_dirs.iter().any(|skip| *skip == name)
+                && !is_output(entry.path())
+                && !other_projects.contains(entry.path())
+        })
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| unix_secs(entry.metadata().ok()?.modified()))
+        .max()
+}
+
+/// Time of the last commit touching `project`, or `None` outside a
+/// repository or without git. Limited to the project's own folder, so a
+/// package in a monorepo is not dated by commits to its siblings.
+fn git_last_commit(project: &Path) -> Option<u64> {
+    let project = project.to_string_lossy();
+    let output = run_tool(
+        "git",
+        &["-C", &project, "log", "-1", "--format=%ct", "--", "."],
+    )?;
+    output.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    fn touch(file: &Path, secs: u64) {
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::File::create(file)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn nested_projects_date_their_parent_and_outputs_do_not() {
+        let dir = tempfile::tempdir().unwrap();
+        let monorepo = dir.path().join("monorepo");
+        let package = monorepo.join("packages/app");
+        touch(&monorepo.join("package.json"), 1_000);
+        touch(&monorepo.join("node_modules/dep/index.js"), 9_000);
+        touch(&package.join("src/App.tsx"), 2_000);
+        touch(&package.join("android/build/out.apk"), 9_000);
+        let other = dir.path().join("other");
+        touch(&other.join("package.json"), 500);
+
+        let activity = projects_activity(&[monorepo.clone(), package.clone(), other.clone()], &[]);
+
+        let newest: Vec<_> = activity.iter().map(|a| a.newest_source_at).collect();
+        assert_eq!(newest, [Some(2_000), Some(2_000), Some(500)]);
+    }
+}
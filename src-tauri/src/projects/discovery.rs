@@ -0,0 +1,311 @@
+use crate::models::{ProjectKind, RnProject, ScanResult};
+use crate::utils::{get_dir_size_observed, SizeObserver};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// How deep below a workspace root projects are looked for.
+const MAX_DEPTH: usize = 6;
+
+/// Folders that never contain a project of their own and can be huge.
+const NEVER_DESCEND: &[&str] = &[
+    "node_modules",
+    "Pods",
+    "build",
+    "DerivedData",
+    "dist",
+    "target",
+];
+
+/// A regenerable folder inside a project: relative path, `file_type` and
+/// owning tool.
+pub const PROJECT_ARTIFACTS: &[(&str, &str, &str)] = &[
+    ("node_modules", "node_modules", "npm / Yarn"),
+    ("ios/Pods", "ios_pods", "CocoaPods"),
+    ("ios/build", "ios_build", "Xcode"),
+    ("android/build", "android_build", "Gradle"),
+    ("android/app/build", "android_app_build", "Gradle"),
+    ("android/.gradle", "android_gradle", "Gradle"),
+    ("android/app/.cxx", "android_cxx", "Android NDK"),
+    (".expo", "expo_state", "Expo"),
+];
+
+fn skip_dir(name: &str) -> bool {
+    name.starts_with('.') || NEVER_DESCEND.contains(&name)
+}
+
+/// Every React Native or Expo project under `roots`, without sizing them.
+/// Projects nested in other projects (monorepo packages) are included.
+pub fn discover_projects(roots: &[PathBuf], observer: &dyn SizeObserver) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    let mut projects = Vec::new();
+
+    for root in roots.iter().filter(|root| root.is_dir()) {
+        let walker = WalkDir::new(root)
+            .max_depth(MAX_DEPTH)
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0
+                    || !entry.file_type().is_dir()
+                    || !skip_dir(&entry.file_name().to_string_lossy())
+            });
+
+        for entry in walker.filter_map(|e| e.ok()) {
+            if observer.is_cancelled() {
+                return projects;
+            }
+            if !entry.file_type().is_dir() || !entry.path().join("package.json").is_file() {
+                continue;
+            }
+
+            let path = entry.path().canonicalize().unwrap_or(entry.into_path());
+            if identify_project(&path).is_some() && seen.insert(path.clone()) {
+                projects.push(path);
+            }
+        }
+    }
+
+    projects
+}
+
+/// The project kind and the marker files that identified `path`, or `None`
+/// when it is not a React Native project.
+///
+/// A `react-native` or `expo` dependency is enough on its own; otherwise
+/// both native folders must be present.
+pub fn identify_project(path: &Path) -> Option<(ProjectKind, Vec<String>)> {
+    let package = read_json(&path.join("package.json"))?;
+    let app_json = read_json(&path.join("app.json"));
+
+    let depends_on = |name: &str| {
+        ["dependencies", "devDependencies", "peerDependencies"]
+            .iter()
+            .any(|section| package[section].get(name).is_some())
+    };
+
+    let mut markers = Vec::new();
+    let has_rn_dependency = depends_on("react-native") || depends_on("expo");
+    if has_rn_dependency {
+        markers.push("package.json".to_string());
+    }
+    if app_json.is_some() {
+        markers.push("app.json".to_string());
+    }
+
+    let podfile = path.join("ios/Podfile").is_file();
+    let settings_gradle = ["android/settings.gradle", "android/settings.gradle.kts"]
+        .iter()
+        .find(|file| path.join(file).is_file());
+    if podfile {
+        markers.push("ios/Podfile".to_string());
+    }
+    if let Some(file) = settings_gradle {
+        markers.push(file.to_string());
+    }
+
+    let has_native_folders = podfile && settings_gradle.is_some();
+    if !has_rn_dependency && !has_native_folders {
+        return None;
+    }
+
+    let is_expo = depends_on("expo")
+        || app_json
+            .as_ref()
+            .is_some_and(|app| app.get("expo").is_some());
+    let kind = if is_expo {
+        ProjectKind::Expo
+    } else {
+        ProjectKind::ReactNative
+    };
+
+    Some((kind, markers))
+}
+
+/// Identifies `path` and sizes its regenerable folders.
+pub fn inventory_project(path: &Path, observer: &dyn SizeObserver) -> Option<RnProject> {
+    let (kind, markers) = identify_project(path)?;
+
+    let name = read_json(&path.join("package.json"))
+        .and_then(|package| package["name"].as_str().map(str::to_string))
+        .unwrap_or_else(|| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+
+    let artifacts: Vec<ScanResult> = PROJECT_ARTIFACTS
+        .par_iter()
+        .map(|(relative, file_type, owner)| (path.join(relative), file_type, owner))
+        .filter(|(artifact, _, _)| artifact.is_dir())
+        .map(|(artifact, file_type, owner)| {
+            let size = get_dir_size_observed(&artifact, observer);
+            ScanResult::from_dir_size(&artifact, file_type, owner, size)
+        })
+        .collect();
+
+    Some(RnProject {
+        path: path.to_string_lossy().to_string(),
+        name,
+        kind,
+        markers,
+        artifacts_size: artifacts.iter().map(|artifact| artifact.size).sum(),
+        artifacts,
+    })
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    let bytes = std::fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::NoProgress;
+
+    fn project(root: &Path, name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = root.join(name);
+        for (file, contents) in files {
+            let file = dir.join(file);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn a_react_native_or_expo_dependency_identifies_a_project() {
+        let root = tempfile::tempdir().unwrap();
+        let bare = project(
+            root.path(),
+            "bare",
+            &[(
+                "package.json",
+                r#"{"devDependencies": {"react-native": "0.74.0"}}"#,
+            )],
+        );
+        let expo = project(
+            root.path(),
+            "expo",
+            &[("package.json", r#"{"dependencies": {"expo": "~51.0.0"}}"#)],
+        );
+        let web = project(
+            root.path(),
+            "web",
+            &[("package.json", r#"{"dependencies": {"react": "18.2.0"}}"#)],
+        );
+
+        assert_eq!(
+            identify_project(&bare),
+            Some((ProjectKind::ReactNative, vec!["package.json".to_string()]))
+        );
+        assert_eq!(
+            identify_project(&expo),
+            Some((ProjectKind::Expo, vec!["package.json".to_string()]))
+        );
+        assert_eq!(identify_project(&web), None);
+    }
+
+    #[test]
+    fn native_folders_identify_a_project_only_together() {
+        let root = tempfile::tempdir().unwrap();
+        let both = project(
+            root.path(),
+            "both",
+            &[
+                ("package.json", "{}"),
+                ("ios/Podfile", ""),
+                ("android/settings.gradle.kts", ""),
+            ],
+        );
+        let ios_only = project(
+            root.path(),
+            "ios-only",
+            &[("package.json", "{}"), ("ios/Podfile", "")],
+        );
+        let android_only = project(
+            root.path(),
+            "android-only",
+            &[("package.json", "{}"), ("android/settings.gradle", "")],
+        );
+
+        assert_eq!(
+            identify_project(&both),
+            Some((
+                ProjectKind::ReactNative,
+                vec![
+                    "ios/Podfile".to_string(),
+                    "android/settings.gradle.kts".to_string()
+                ]
+            ))
+        );
+        assert_eq!(identify_project(&ios_only), None);
+        assert_eq!(identify_project(&android_only), None);
+    }
+
+    #[test]
+    fn an_expo_section_in_app_json_makes_a_project_expo() {
+        let root = tempfile::tempdir().unwrap();
+        let expo = project(
+            root.path(),
+            "expo",
+            &[
+                (
+                    "package.json",
+                    r#"{"dependencies": {"react-native": "0.74.0"}}"#,
+                ),
+                ("app.json", r#"{"expo": {"name": "app"}}"#),
+            ],
+        );
+        let bare = project(
+            root.path(),
+            "bare",
+            &[
+                (
+                    "package.json",
+                    r#"{"dependencies": {"react-native": "0.74.0"}}"#,
+                ),
+                ("app.json", r#"{"name": "app"}"#),
+            ],
+        );
+        // app.json alone is not enough.
+        let app_json_only = project(
+            root.path(),
+            "app-json-only",
+            &[("package.json", "{}"), ("app.json", r#"{"expo": {}}"#)],
+        );
+
+        let markers = vec!["package.json".to_string(), "app.json".to_string()];
+        assert_eq!(
+            identify_project(&expo),
+            Some((ProjectKind::Expo, markers.clone()))
+        );
+        assert_eq!(
+            identify_project(&bare),
+            Some((ProjectKind::ReactNative, markers))
+        );
+        assert_eq!(identify_project(&app_json_only), None);
+    }
+
+    #[test]
+    fn discovery_finds_nested_projects_but_not_inside_skipped_folders() {
+        let root = tempfile::tempdir().unwrap();
+        let rn = r#"{"dependencies": {"react-native": "0.74.0"}}"#;
+        let monorepo = project(root.path(), "monorepo", &[("package.json", rn)]);
+        let package = project(&monorepo, "packages/app", &[("package.json", rn)]);
+        project(&monorepo, "node_modules/dep", &[("package.json", rn)]);
+        project(&monorepo, ".cache/copy", &[("package.json", rn)]);
+        project(root.path(), "web", &[("package.json", "{}")]);
+
+        let mut found = discover_projects(&[root.path().to_path_buf()], &NoProgress);
+        found.sort();
+
+        let mut expected = vec![
+            monorepo.canonicalize().unwrap(),
+            package.canonicalize().unwrap(),
+        ];
+        expected.sort();
+        assert_eq!(found, expected);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::models::{DuplicatedPackage, DuplicationReport};
+use crate::utils::{get_dir_size_observed, SizeObserver};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A package installed directly in a `node_modules` folder.
+#[derive(Debug, Clone)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub version: String,
+    pub path: PathBuf,
+}
+
+/// The packages at the top of `node_modules`, scoped ones included.
+///
+/// Symlinked entries are skipped: with pnpm or `npm link` they point into a
+/// shared store and are not copies of their own.
+pub fn installed_packages(node_modules: &Path) -> Vec<InstalledPackage> {
+    let mut packages = Vec::new();
+
+    for (path, _) in package_dirs(node_modules) {
+        let scope = path.file_name().unwrap_or_default().to_string_lossy();
+        if scope.starts_with('@') {
+            packages.extend(
+                package_dirs(&path)
+                    .into_iter()
+                    .filter_map(|(path, _)| read_package(&path)),
+            );
+        } else if let Some(package) = read_package(&path) {
+            packages.push(package);
+        }
+    }
+
+    packages
+}
+
+fn package_dirs(dir: &Path) -> Vec<(PathBuf, String)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| {
+            (
+                entry.path(),
+                entry.file_name().to_string_lossy().to_string(),
+            )
+        })
+        .filter(|(_, name)| !name.starts_with('.'))
+        .collect()
+}
+
+fn read_package(path: &Path) -> Option<InstalledPackage> {
+    let bytes = std::fs::read(path.join("package.json")).ok()?;
+    let manifest: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+
+    Some(InstalledPackage {
+        name: manifest["name"].as_str()?.to_string(),
+        version: manifest["version"].as_str()?.to_string(),
+        path: path.to_path_buf(),
+    })
+}
+
+/// Groups the packages of every folder in `node_modules_dirs` by
+/// `name@version` and prices the copies beyond the first.
+///
+/// Copies are sized by what deleting them would free, so files already
+/// hard-linked between projects do not count as duplicated. Once `observer`
+/// is cancelled the remaining packages are left out.
+pub fn analyze_duplication(
+    node_modules_dirs: &[PathBuf],
+    observer: &dyn SizeObserver,
+) -> DuplicationReport {
+    let sized: Vec<(InstalledPackage, u64)> = node_modules_dirs
+        .par_iter()
+        .flat_map(|dir| installed_packages(dir))
+        .filter(|_| !observer.is_cancelled())
+        .map(|package| {
+            let size = get_dir_size_observed(&package.path, observer).reclaimable_bytes();
+            (package, size)
+        })
+        .collect();
+
+    let packages_scanned = sized.len();
+    let mut groups: HashMap<(String, String), Vec<(PathBuf, u64)>> = HashMap::new();
+    for (package, size) in sized {
+        groups
+            .entry((package.name, package.version))
+            .or_default()
+            .push((package.path, size));
+    }
+
+    let mut report = DuplicationReport {
+        node_modules_scanned: node_modules_dirs.len(),
+        packages_scanned,
+        ..Default::default()
+    };
+
+    for ((name, version), copies) in groups {
+        let total_size: u64 = copies.iter().map(|(_, size)| size).sum();
+        let largest = copies
+            .iter()
+            .map(|(_, size)| *size)
+            .max()
+            .unwrap_or_default();
+        report.total_size += total_size;
+        report.unique_size += largest;
+
+        if copies.len() > 1 {
+            report.duplicates.push(DuplicatedPackage {
+                name,
+                version,
+                copies: copies.len(),
+                total_size,
+                wasted_size: total_size - largest,
+                paths: copies
+                    .iter()
+                    .map(|(path, _)| path.to_string_lossy().to_string())
+                    .collect(),
+            });
+        }
+    }
+
+    report
+        .duplicates
+        .sort_by_key(|package| std::cmp::Reverse(package.wasted_size));
+    report.store_savings = report.total_size - report.unique_size;
+    report
+}
@@ -0,0 +1,9 @@
+pub mod activity;
+pub mod discovery;
+pub mod duplication;
+pub mod workspace_config;
+
+pub use activity::*;
+pub use discovery::*;
+pub use duplication::*;
+pub use workspace_config::*;
@@ -0,0 +1,69 @@
+use crate::models::WorkspaceConfig;
+use crate::utils::set_tool_queries_enabled;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+/// Stored next to the size index in the app data dir.
+pub const WORKSPACE_CONFIG_FILE: &str = "workspace_config.json";
+
+static CONFIG_FILE: OnceLock<PathBuf> = OnceLock::new();
+static CONFIG: OnceLock<RwLock<WorkspaceConfig>> = OnceLock::new();
+
+fn config_slot() -> &'static RwLock<WorkspaceConfig> {
+    CONFIG.get_or_init(Default::default)
+}
+
+/// Loads the config from `file`, keeping the defaults when it is missing or
+/// unreadable. Only the first call has any effect.
+pub fn load_workspace_config(file: PathBuf) {
+    if CONFIG_FILE.set(file.clone()).is_err() {
+        return;
+    }
+
+    if let Some(config) = std::fs::read(&file)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<WorkspaceConfig>(&bytes).ok())
+    {
+        set_tool_queries_enabled(config.tool_queries);
+        *config_slot().write().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+}
+
+pub fn workspace_config() -> WorkspaceConfig {
+    config_slot()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Replaces the config and writes it back when a config file was loaded.
+pub fn save_workspace_config(config: WorkspaceConfig) -> Result<(), String> {
+    let bytes = serde_json::to_vec_pretty(&config)
+        .map_err(|e| format!("Failed to encode workspace config: {e}"))?;
+    set_tool_queries_enabled(config.tool_queries);
+    *config_slot().write().unwrap_or_else(|e| e.into_inner()) = config;
+
+    let Some(file) = CONFIG_FILE.get() else {
+        return Ok(());
+    };
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    std::fs::write(file, bytes).map_err(|e| format!("Failed to save workspace config: {e}"))
+}
+
+/// The configured roots as absolute paths; `~/` and relative roots are taken
+/// from the home directory.
+pub fn workspace_roots(config: &WorkspaceConfig) -> Vec<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_default();
+
+    config
+        .roots
+        .iter()
+        .map(|root| {
+            let root = root.strip_prefix("~/").unwrap_or(root);
+            home.join(root)
+        })
+        .collect()
+}
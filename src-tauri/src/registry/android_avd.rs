@@ -0,0 +1,335 @@
+use crate::models::{AndroidVirtualDevice, ScanResult};
+use crate::registry::{running_daemons, Location, Platform};
+use crate::utils::{get_dir_size_observed, unix_secs, DirSize, SizeObserver};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const OWNER: &str = "Android Emulator";
+
+/// Where the emulator keeps AVDs, by the precedence it applies itself.
+const AVD_HOME: &[Location] = &[
+    Location::env("ANDROID_AVD_HOME", ""),
+    Location::env("ANDROID_USER_HOME", "avd").unless_env(&["ANDROID_AVD_HOME"]),
+    Location::env("ANDROID_SDK_HOME", ".android/avd")
+        .unless_env(&["ANDROID_AVD_HOME", "ANDROID_USER_HOME"]),
+    Location::home(Platform::Any, ".android/avd").unless_env(&[
+        "ANDROID_AVD_HOME",
+        "ANDROID_USER_HOME",
+        "ANDROID_SDK_HOME",
+    ]),
+];
+
+pub fn avd_home() -> Option<PathBuf> {
+    let location = AVD_HOME.iter().find(|location| location.applies())?;
+    location.pattern_path().ok()
+}
+
+/// The `key=value` pairs of an AVD `.ini` or `config.ini` file.
+pub fn parse_ini(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// A `disk.dataPartition.size` in bytes: plain bytes, or with a `K`, `M`
+/// or `G` suffix, optionally followed by `B`.
+pub fn parse_disk_size(value: &str) -> Option<u64> {
+    let value = value.trim().trim_end_matches(['B', 'b']);
+    let (number, unit) = match value.char_indices().last()? {
+        (at, unit) if unit.is_ascii_alphabetic() => (&value[..at], unit),
+        _ => (value, ' '),
+    };
+
+    let multiplier = match unit.to_ascii_uppercase() {
+        ' ' => 1,
+        'K' => 1024,
+        'M' => 1024 * 1024,
+        'G' => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    number.trim().parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// The API level in `system-images/android-34/google_apis/x86_64/` or
+/// `android-34`.
+fn api_level(value: &str) -> Option<String> {
+    value
+        .split(['/', '\\'])
+        .find_map(|segment| segment.strip_prefix("android-"))
+        .map(str::to_string)
+}
+
+/// The AVD an emulator command line runs, from `-avd <id>` or `@<id>`.
+pub fn emulator_avd(command: &str) -> Option<String> {
+    let mut args = command.split_whitespace();
+    while let Some(arg) = args.next() {
+        if arg == "-avd" {
+            return args.next().map(str::to_string);
+        }
+        if let Some(id) = arg.strip_prefix('@').filter(|id| !id.is_empty()) {
+            return Some(id.to_string());
+        }
+    }
+    None
+}
+
+/// The ids of the AVDs a running emulator has open. Lock files left in the
+/// AVD by a crashed emulator say nothing, so the processes are asked.
+pub fn running_avds() -> HashSet<String> {
+    running_daemons()
+        .iter()
+        .filter(|daemon| daemon.id == "android_emulator")
+        .filter_map(|daemon| emulator_avd(&daemon.command))
+        .collect()
+}
+
+/// Every AVD listed in `home`, sized, with its snapshots and user data
+/// broken out.
+pub fn android_virtual_devices(
+    home: &Path,
+    observer: &dyn SizeObserver,
+) -> Vec<AndroidVirtualDevice> {
+    let Ok(entries) = std::fs::read_dir(home) else {
+        return Vec::new();
+    };
+
+    let mut ini_files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "ini"))
+        .collect();
+    ini_files.sort();
+
+    let running = running_avds();
+    let mut devices = Vec::new();
+    for ini_path in ini_files {
+        if observer.is_cancelled() {
+            break;
+        }
+        if let Some(device) = inventory_avd(&ini_path, home, &running, observer) {
+            devices.push(device);
+        }
+    }
+    devices
+}
+
+/// The folder the `<id>.ini` pointer at `ini_path` refers to, if it exists.
+pub fn avd_dir(ini_path: &Path, home: &Path) -> Option<PathBuf> {
+    let id = ini_path.file_stem()?.to_string_lossy().to_string();
+    let pointer = parse_ini(&std::fs::read_to_string(ini_path).ok()?);
+
+    // `path` is absolute; `path.rel` is relative to the AVD home's parent.
+    [
+        pointer.get("path").map(PathBuf::from),
+        pointer
+            .get("path.rel")
+            .and_then(|rel| Some(home.parent()?.join(rel))),
+        Some(home.join(format!("{id}.avd"))),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|dir| dir.is_dir())
+}
+
+/// Whether `dir` looks like an AVD folder: named `<id>.avd` and holding a
+/// `config.ini`. The pointer's `path` is trusted only this far before a
+/// folder is deleted recursively.
+pub fn is_avd_dir(dir: &Path) -> bool {
+    dir.extension().is_some_and(|ext| ext == "avd") && dir.join("config.ini").is_file()
+}
+
+/// Reads the `<id>.ini` pointer at `ini_path` and the `config.ini` of the
+/// AVD it points to. `None` when the AVD folder is gone. AVDs in `running`
+/// are reported but not deletable.
+pub fn inventory_avd(
+    ini_path: &Path,
+    home: &Path,
+    running: &HashSet<String>,
+    observer: &dyn SizeObserver,
+) -> Option<AndroidVirtualDevice> {
+    let id = ini_path.file_stem()?.to_string_lossy().to_string();
+    let pointer = parse_ini(&std::fs::read_to_string(ini_path).ok()?);
+    let avd_dir = avd_dir(ini_path, home)?;
+
+    let config = std::fs::read_to_string(avd_dir.join("config.ini"))
+        .map(|contents| parse_ini(&contents))
+        .unwrap_or_default();
+    let system_image = config
+        .get("image.sysdir.1")
+        .map(|dir| dir.trim_end_matches(['/', '\\']).to_string());
+    let api_level = system_image
+        .as_deref()
+        .and_then(api_level)
+        .or_else(|| pointer.get("target").and_then(|target| api_level(target)));
+
+    // The folder is walked once: snapshots and user data are sized on their
+    // own and everything else is added to them to make up the total.
+    let mut size = DirSize::default();
+    let mut snapshots = Vec::new();
+    let mut user_data = Vec::new();
+    for (name, path) in dir_entries(&avd_dir)? {
+        if name == "snapshots" && path.is_dir() && !path.is_symlink() {
+            for (_, path) in dir_entries(&path).unwrap_or_default() {
+                let part = get_dir_size_observed(&path, observer);
+                size = size.merge(part.clone());
+                if path.is_dir() && !path.is_symlink() {
+                    snapshots.push(ScanResult::from_dir_size(
+                        &path,
+                        "avd_snapshot",
+                        OWNER,
+                        part,
+                    ));
+                }
+            }
+        } else if name.starts_with("userdata-qemu.img") {
+            let part = get_dir_size_observed(&path, observer);
+            size = size.merge(part.clone());
+            user_data.push(ScanResult::from_dir_size(
+                &path,
+                "avd_user_data",
+                OWNER,
+                part,
+            ));
+        } else {
+            size = size.merge(get_dir_size_observed(&path, observer));
+        }
+    }
+    snapshots.sort_by(|a, b| a.path.cmp(&b.path));
+    user_data.sort_by(|a, b| a.path.cmp(&b.path));
+    size.modified_at = unix_secs(std::fs::metadata(&avd_dir).and_then(|m| m.modified()));
+
+    let last_used_at = size.newest_file_at;
+    let avd = ScanResult {
+        version: api_level.clone(),
+        last_used_at,
+        ..ScanResult::from_dir_size(&avd_dir, "avd", OWNER, size)
+    };
+
+    let running = running.contains(&id);
+    let mut device = AndroidVirtualDevice {
+        name: config
+            .get("avd.ini.displayname")
+            .cloned()
+            .unwrap_or_else(|| id.replace('_', " ")),
+        id,
+        ini_path: ini_path.to_string_lossy().to_string(),
+        api_level,
+        system_image,
+        device: config.get("hw.device.name").cloned(),
+        disk_size: config
+            .get("disk.dataPartition.size")
+            .and_then(|size| parse_disk_size(size)),
+        avd,
+        snapshots,
+        user_data,
+        last_used_at,
+        running,
+    };
+
+    if running {
+        let results = std::iter::once(&mut device.avd)
+            .chain(device.snapshots.iter_mut())
+            .chain(device.user_data.iter_mut());
+        for result in results {
+            result.can_delete = false;
+        }
+    }
+
+    Some(device)
+}
+
+fn dir_entries(dir: &Path) -> Option<Vec<(String, PathBuf)>> {
+    let entries = std::fs::read_dir(dir).ok()?;
+    Some(
+        entries
+            .filter_map(|e| e.ok())
+            .map(|entry| {
+                (
+                    entry.file_name().to_string_lossy().to_string(),
+                    entry.path(),
+                )
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::NoProgress;
+
+    #[test]
+    fn reads_the_avd_from_emulator_command_lines() {
+        assert_eq!(
+            emulator_avd("qemu-system-x86_64 -netdelay none -avd Pixel_7_API_34 -no-snapshot"),
+            Some("Pixel_7_API_34".to_string())
+        );
+        assert_eq!(
+            emulator_avd("qemu-system-aarch64 @Pixel_Tablet"),
+            Some("Pixel_Tablet".to_string())
+        );
+        assert_eq!(emulator_avd("qemu-system-x86_64 -avd"), None);
+        assert_eq!(emulator_avd("qemu-system-x86_64 -version"), None);
+    }
+
+    #[test]
+    fn sizes_the_avd_once_from_its_parts() {
+        let temp = tempfile::tempdir().unwrap();
+        let home = temp.path().to_path_buf();
+        let avd = home.join("Pixel.avd");
+        std::fs::create_dir_all(avd.join("snapshots/default_boot")).unwrap();
+        let config = "hw.device.name=pixel_7\n";
+        std::fs::write(home.join("Pixel.ini"), "target=android-34\n").unwrap();
+        std::fs::write(avd.join("config.ini"), config).unwrap();
+        std::fs::write(avd.join("userdata-qemu.img"), vec![0; 4096]).unwrap();
+        std::fs::write(avd.join("snapshots/default_boot/ram.bin"), vec![0; 8192]).unwrap();
+        std::fs::write(avd.join("Pixel.avd.lock"), "").unwrap();
+
+        let ini = home.join("Pixel.ini");
+        let device = inventory_avd(&ini, &home, &HashSet::new(), &NoProgress).unwrap();
+        assert_eq!(device.avd.size, 4096 + 8192 + config.len() as u64);
+        assert_eq!(device.api_level.as_deref(), Some("34"));
+        assert_eq!(device.snapshots.len(), 1);
+        assert_eq!(device.snapshots[0].size, 8192);
+        assert_eq!(device.user_data[0].size, 4096);
+        assert!(!device.running && device.avd.can_delete);
+
+        let running = HashSet::from(["Pixel".to_string()]);
+        let device = inventory_avd(&ini, &home, &running, &NoProgress).unwrap();
+        assert!(device.running && !device.avd.can_delete);
+    }
+
+    #[test]
+    fn disk_sizes_that_overflow_are_unparseable() {
+        assert_eq!(parse_disk_size("800M"), Some(800 * 1024 * 1024));
+        assert_eq!(parse_disk_size("2GB"), Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_disk_size("18446744073709551615G"), None);
+    }
+
+    #[test]
+    fn only_avd_folders_with_a_config_are_deletable() {
+        let temp = tempfile::tempdir().unwrap();
+        let home = temp.path().join("avd");
+        let avd = home.join("Pixel.avd");
+        std::fs::create_dir_all(&avd).unwrap();
+        std::fs::write(
+            home.join("Pixel.ini"),
+            format!("path={}\n", temp.path().display()),
+        )
+        .unwrap();
+
+        let dir = avd_dir(&home.join("Pixel.ini"), &home).unwrap();
+        assert_eq!(dir, temp.path());
+        assert!(!is_avd_dir(&dir));
+        assert!(!is_avd_dir(&avd));
+
+        std::fs::write(avd.join("config.ini"), "").unwrap();
+        assert!(is_avd_dir(&avd));
+    }
+}
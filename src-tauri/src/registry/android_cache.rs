@@ -0,0 +1,222 @@
+use crate::models::{CacheCategory, ScanResult};
+use crate::registry::{
+    CacheDefinition, CacheTarget, CandidatePath, Location, Platform, ScanContext,
+};
+use crate::utils::{get_candidate_size, subdirs};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const GRADLE_HOME: &[Location] = &[
+    Location::env("GRADLE_USER_HOME", ""),
+    Location::home(Platform::Any, ".gradle").unless_env(&["GRADLE_USER_HOME"]),
+];
+
+/// Where a project pins its Gradle version, relative to the project.
+const WRAPPER_PROPERTIES: &[&str] = &[
+    "android/gradle/wrapper/gradle-wrapper.properties",
+    "gradle/wrapper/gradle-wrapper.properties",
+];
+
+/// The Android category, with the Gradle user home split into one entry per
+/// cache, distribution and daemon version instead of two opaque folders.
+pub struct AndroidCache {
+    pub base: CacheDefinition,
+}
+
+/// A part of the Gradle user home, tied to a Gradle version when it has one.
+#[derive(Debug, Clone)]
+pub struct GradleEntry {
+    pub path: PathBuf,
+    pub component: &'static str,
+    pub version: Option<String>,
+}
+
+pub fn gradle_user_home() -> Option<CandidatePath> {
+    let location = GRADLE_HOME.iter().find(|location| location.applies())?;
+
+    Some(CandidatePath {
+        path: location.pattern_path().ok()?,
+        source: location.source(),
+    })
+}
+
+/// The breakdown of `home`: `caches/<version>`, `caches/transforms-*`,
+/// `caches/modules-2`, `caches/build-cache-*`, any other `caches/*` folder
+/// (`jars-*`, `journal-*`, `kotlin-dsl`, ...), `wrapper/dists/gradle-*`,
+/// `daemon/<version>` and `jdks`, whichever exist.
+pub fn gradle_home_entries(home: &Path) -> Vec<GradleEntry> {
+    let mut entries = Vec::new();
+    let entry = |path: PathBuf, component, version| GradleEntry {
+        path,
+        component,
+        version,
+    };
+
+    for (name, path) in subdirs(&home.join("caches")) {
+        if is_version(&name) {
+            entries.push(entry(path, "gradle_version_cache", Some(name)));
+        } else if name.starts_with("transforms-") {
+            entries.push(entry(path, "gradle_transforms", None));
+        } else if name == "modules-2" {
+            entries.push(entry(path, "gradle_modules", None));
+        } else if name.starts_with("build-cache-") {
+            entries.push(entry(path, "gradle_build_cache", None));
+        } else {
+            entries.push(entry(path, "gradle_other", None));
+        }
+    }
+
+    for (name, path) in subdirs(&home.join("wrapper/dists")) {
+        if let Some(version) = dist_version(&name) {
+            entries.push(entry(path, "gradle_wrapper_dist", Some(version)));
+        }
+    }
+
+    for (name, path) in subdirs(&home.join("daemon")) {
+        if is_version(&name) {
+            entries.push(entry(path, "gradle_daemon", Some(name)));
+        }
+    }
+
+    let jdks = home.join("jdks");
+    if jdks.is_dir() {
+        entries.push(entry(jdks, "gradle_jdks", None));
+    }
+
+    entries
+}
+
+fn is_version(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// `gradle-8.3-all` and `gradle-8.3-bin` are both distributions of 8.3.
+fn dist_version(name: &str) -> Option<String> {
+    let version = name.strip_prefix("gradle-")?;
+    let version = version
+        .strip_suffix("-all")
+        .or_else(|| version.strip_suffix("-bin"))
+        .unwrap_or(version);
+
+    is_version(version).then(|| version.to_string())
+}
+
+/// The Gradle version in a `gradle-wrapper.properties`, read from the
+/// distribution file name in `distributionUrl`.
+pub fn parse_wrapper_version(properties: &str) -> Option<String> {
+    let url = properties.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "distributionUrl").then(|| value.trim())
+    })?;
+
+    let file_name = url.rsplit('/').next()?;
+    dist_version(file_name.strip_suffix(".zip")?)
+}
+
+/// Gradle versions pinned by `projects`, each with the projects pinning it.
+pub fn gradle_versions_in_use(projects: &[PathBuf]) -> HashMap<String, Vec<String>> {
+    let mut versions: HashMap<String, Vec<String>> = HashMap::new();
+
+    for project in projects {
+        let pinned = WRAPPER_PROPERTIES
+            .iter()
+            .filter_map(|file| std::fs::read_to_string(project.join(file)).ok())
+            .find_map(|properties| parse_wrapper_version(&properties));
+
+        if let Some(version) = pinned {
+            versions
+                .entry(version)
+                .or_default()
+                .push(project.to_string_lossy().to_string());
+        }
+    }
+
+    versions
+}
+
+impl CacheTarget for AndroidCache {
+    fn base(&self) -> &CacheDefinition {
+        &self.base
+    }
+
+    fn candidates(&self) -> Result<Vec<CandidatePath>, String> {
+        let mut candidates = self.base.candidates()?;
+
+        if let Some(home) = gradle_user_home() {
+            candidates.extend(gradle_home_entries(&home.path).into_iter().map(|entry| {
+                CandidatePath {
+                    path: entry.path,
+                    source: home.source.clone(),
+                }
+            }));
+        }
+
+        Ok(candidates)
+    }
+
+    fn watch_roots(&self) -> Vec<CandidatePath> {
+        let mut roots = self.base.watch_roots();
+        if let Some(home) = gradle_user_home() {
+            roots.extend(
+                ["caches", "wrapper/dists", "daemon", "jdks"]
+                    .iter()
+                    .map(|dir| CandidatePath {
+                        path: home.path.join(dir),
+                        source: home.source.clone(),
+                    }),
+            );
+        }
+        roots
+    }
+
+    /// Entries tied to a Gradle version a project still pins are reported but
+    /// not deletable.
+    fn scan(&self, context: &ScanContext) -> Result<Vec<ScanResult>, String> {
+        let mut results = self.base.scan(context)?;
+        let Some(home) = gradle_user_home() else {
+            return Ok(results);
+        };
+
+        let entries = gradle_home_entries(&home.path);
+        let in_use = if entries.iter().any(|entry| entry.version.is_some()) {
+            gradle_versions_in_use(context.projects())
+        } else {
+            HashMap::new()
+        };
+
+        for entry in entries {
+            if context.observer.is_cancelled() {
+                break;
+            }
+
+            let used_by = entry
+                .version
+                .as_ref()
+                .and_then(|version| in_use.get(version))
+                .cloned()
+                .unwrap_or_default();
+            let size = get_candidate_size(&entry.path, context.observer);
+
+            results.push(ScanResult {
+                can_delete: used_by.is_empty(),
+                path_source: home.source.clone(),
+                component: entry.component.to_string(),
+                version: entry.version,
+                used_by,
+                ..ScanResult::from_dir_size(&entry.path, self.id(), "Gradle", size)
+            });
+        }
+
+        Ok(results)
+    }
+
+    fn describe(&self) -> CacheCategory {
+        let mut category = self.base.describe();
+        if let Some(home) = gradle_user_home() {
+            category
+                .locations
+                .push(home.path.to_string_lossy().to_string());
+        }
+        category
+    }
+}
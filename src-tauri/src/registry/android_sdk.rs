@@ -0,0 +1,427 @@
+use crate::models::{CacheCategory, ScanResult};
+use crate::registry::{CacheDefinition, CacheTarget, CandidatePath, ScanContext};
+use crate::utils::{get_candidate_size, subdirs};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Gradle files that set the SDK versions, relative to the project.
+const GRADLE_FILES: &[&str] = &[
+    "android/build.gradle",
+    "android/build.gradle.kts",
+    "android/app/build.gradle",
+    "android/app/build.gradle.kts",
+];
+
+/// Where Expo prebuild and expo-build-properties set `android.*` values the
+/// Gradle files read through `findProperty`, relative to the project.
+const GRADLE_PROPERTIES: &str = "android/gradle.properties";
+
+const SDK_KEYS: &[&str] = &[
+    "compileSdkVersion",
+    "compileSdk",
+    "buildToolsVersion",
+    "ndkVersion",
+];
+
+/// Components projects reference from their Gradle files. Anything else in
+/// the SDK (system images, CMake, the emulator) is reported but never deletable
+/// from here.
+const REFERENCED_COMPONENTS: &[&str] = &["platform", "build_tools", "ndk"];
+
+/// The Android SDK category, sized per installed package rather than as one
+/// folder, with packages no discovered project builds against deletable.
+pub struct AndroidSdk {
+    pub base: CacheDefinition,
+}
+
+/// One installed SDK package.
+#[derive(Debug, Clone)]
+pub struct SdkComponent {
+    pub path: PathBuf,
+    pub component: &'static str,
+    pub version: Option<String>,
+}
+
+/// The SDK versions a project's Gradle files ask for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SdkReferences {
+    pub compile_sdk: Vec<String>,
+    pub build_tools: Vec<String>,
+    pub ndk: Vec<String>,
+}
+
+/// The packages under the SDK root `root`: `build-tools/<version>`,
+/// `platforms/android-<api>`, `system-images/android-<api>/<tag>/<abi>`,
+/// `ndk/<version>`, `ndk-bundle`, `cmake/<version>` and `emulator`.
+pub fn sdk_components(root: &Path) -> Vec<SdkComponent> {
+    let mut components = Vec::new();
+    let component = |path: PathBuf, component, version| SdkComponent {
+        path,
+        component,
+        version,
+    };
+
+    for (name, path) in subdirs(&root.join("build-tools")) {
+        components.push(component(path, "build_tools", Some(name)));
+    }
+
+    for (name, path) in subdirs(&root.join("platforms")) {
+        let api = name.strip_prefix("android-").map(str::to_string);
+        components.push(component(path, "platform", api));
+    }
+
+    for (name, api_dir) in subdirs(&root.join("system-images")) {
+        let api = name.strip_prefix("android-");
+        for (_, tag_dir) in subdirs(&api_dir) {
+            for (_, abi_dir) in subdirs(&tag_dir) {
+                components.push(component(abi_dir, "system_image", api.map(str::to_string)));
+            }
+        }
+    }
+
+    for (name, path) in subdirs(&root.join("ndk")) {
+        components.push(component(path, "ndk", Some(name)));
+    }
+    let ndk_bundle = root.join("ndk-bundle");
+    if ndk_bundle.is_dir() {
+        let version = package_revision(&ndk_bundle);
+        components.push(component(ndk_bundle, "ndk", version));
+    }
+
+    for (name, path) in subdirs(&root.join("cmake")) {
+        components.push(component(path, "cmake", Some(name)));
+    }
+
+    let emulator = root.join("emulator");
+    if emulator.is_dir() {
+        let version = package_revision(&emulator);
+        components.push(component(emulator, "emulator", version));
+    }
+
+    components
+}
+
+fn package_revision(package: &Path) -> Option<String> {
+    let properties = std::fs::read_to_string(package.join("source.properties")).ok()?;
+    parse_source_properties(&properties)
+}
+
+/// The `Pkg.Revision` of an SDK package's `source.properties`.
+pub fn parse_source_properties(properties: &str) -> Option<String> {
+    properties.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "Pkg.Revision").then(|| value.trim().to_string())
+    })
+}
+
+/// The `key=value` pairs of a `gradle.properties` file.
+pub fn parse_gradle_properties(properties: &str) -> HashMap<String, String> {
+    properties
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with(['#', '!']))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Reads `compileSdkVersion`/`compileSdk`, `buildToolsVersion` and
+/// `ndkVersion` from a Groovy or Kotlin Gradle file. Literal values are
+/// picked up; `findProperty('android.compileSdkVersion') ?: '34'` takes the
+/// value from `properties` when set there and the default otherwise. The
+/// `rootProject.ext` values app files refer to are literals in the root
+/// file, which is read as well.
+pub fn parse_gradle_sdk_references(
+    gradle: &str,
+    properties: &HashMap<String, String>,
+) -> SdkReferences {
+    let mut references = SdkReferences::default();
+
+    for line in gradle.lines() {
+        let line = line.trim();
+        let Some((key, value)) = SDK_KEYS
+            .iter()
+            .find_map(|key| Some((*key, gradle_value(line.strip_prefix(key)?, properties)?)))
+        else {
+            continue;
+        };
+
+        let list = match key {
+            "buildToolsVersion" => &mut references.build_tools,
+            "ndkVersion" => &mut references.ndk,
+            _ => &mut references.compile_sdk,
+        };
+        let value = value.strip_prefix("android-").unwrap_or(value).to_string();
+        if !list.contains(&value) {
+            list.push(value);
+        }
+    }
+
+    references
+}
+
+/// The version after a Gradle key, in `= "34.0.0"`, ` 34`, `("34.0.0")`
+/// or `(34)` form, or that of a `findProperty` expression.
+fn gradle_value<'a>(rest: &'a str, properties: &'a HashMap<String, String>) -> Option<&'a str> {
+    // Otherwise the key was only the start of a longer name.
+    if !rest.starts_with([' ', '\t', '=', '(']) {
+        return None;
+    }
+
+    version_literal(rest).or_else(|| property_value(rest, properties))
+}
+
+/// The value of `findProperty('<name>') ?: '<default>'`: the property when
+/// it is set, the literal default otherwise.
+fn property_value<'a>(text: &'a str, properties: &'a HashMap<String, String>) -> Option<&'a str> {
+    let name = text.split_once("findProperty(")?.1;
+    let name = name.trim_start_matches(['"', '\'']);
+    let name = &name[..name.find(['"', '\'', ')'])?];
+
+    match properties.get(name) {
+        Some(value) => version_literal(value),
+        None => version_literal(text.rsplit_once("?:")?.1),
+    }
+}
+
+/// A version literal at the start of `text`, quoted or not.
+fn version_literal(text: &str) -> Option<&str> {
+    let value = text.trim_start_matches([' ', '\t', '=', '(', '"', '\'']);
+    let end = value
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-'))
+        .unwrap_or(value.len());
+    let value = &value[..end];
+
+    let api = value.strip_prefix("android-").unwrap_or(value);
+    api.starts_with(|c: char| c.is_ascii_digit())
+        .then_some(value)
+}
+
+/// What the discovered projects build against.
+#[derive(Debug, Clone, Default)]
+pub struct SdkUsage {
+    /// Referenced packages, keyed by component and version, each with the
+    /// projects referencing it.
+    pub in_use: HashMap<(&'static str, String), Vec<String>>,
+    /// Components some project builds with without a version its Gradle
+    /// files name, leaving the Android Gradle plugin to pick one. None of
+    /// their packages can be ruled out.
+    pub unknown: HashSet<&'static str>,
+}
+
+/// The SDK packages `projects` build against. Projects without Gradle files
+/// (Expo projects before prebuild) do not build against the SDK. When no
+/// project does, every component is unknown.
+pub fn sdk_components_in_use(projects: &[PathBuf]) -> SdkUsage {
+    let mut usage = SdkUsage::default();
+    let mut native_projects = 0;
+
+    for project in projects {
+        let gradle_files: Vec<String> = GRADLE_FILES
+            .iter()
+            .filter_map(|file| std::fs::read_to_string(project.join(file)).ok())
+            .collect();
+        if gradle_files.is_empty() {
+            continue;
+        }
+        native_projects += 1;
+
+        let properties = std::fs::read_to_string(project.join(GRADLE_PROPERTIES))
+            .map(|properties| parse_gradle_properties(&properties))
+            .unwrap_or_default();
+        let mut references = SdkReferences::default();
+        for gradle in &gradle_files {
+            let found = parse_gradle_sdk_references(gradle, &properties);
+            references.compile_sdk.extend(found.compile_sdk);
+            references.build_tools.extend(found.build_tools);
+            references.ndk.extend(found.ndk);
+        }
+
+        let project = project.to_string_lossy().to_string();
+        for (component, versions) in [
+            ("platform", references.compile_sdk),
+            ("build_tools", references.build_tools),
+            ("ndk", references.ndk),
+        ] {
+            if versions.is_empty() {
+                usage.unknown.insert(component);
+            }
+            for version in versions {
+                let users = usage.in_use.entry((component, version)).or_default();
+                if !users.contains(&project) {
+                    users.push(project.clone());
+                }
+            }
+        }
+    }
+
+    if native_projects == 0 {
+        usage.unknown.extend(REFERENCED_COMPONENTS);
+    }
+    usage
+}
+
+/// The SDK roots that exist, first one per overlapping set.
+fn sdk_roots(base: &CacheDefinition) -> Vec<CandidatePath> {
+    base.candidates()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|root| root.path.is_dir())
+        .collect()
+}
+
+impl CacheTarget for AndroidSdk {
+    fn base(&self) -> &CacheDefinition {
+        &self.base
+    }
+
+    /// The packages, never the SDK roots themselves.
+    fn candidates(&self) -> Result<Vec<CandidatePath>, String> {
+        Ok(sdk_roots(&self.base)
+            .into_iter()
+            .flat_map(|root| {
+                sdk_components(&root.path)
+                    .into_iter()
+                    .map(move |component| CandidatePath {
+                        path: component.path,
+                        source: root.source.clone(),
+                    })
+            })
+            .collect())
+    }
+
+    /// Platforms, build tools and NDKs a project references are reported but
+    /// not deletable, as is every package of a component whose version some
+    /// project leaves to the Android Gradle plugin.
+    fn scan(&self, context: &ScanContext) -> Result<Vec<ScanResult>, String> {
+        let roots = sdk_roots(&self.base);
+        let usage = if roots.is_empty() {
+            SdkUsage::default()
+        } else {
+            sdk_components_in_use(context.projects())
+        };
+
+        let mut results = Vec::new();
+        for root in roots {
+            for component in sdk_components(&root.path) {
+                if context.observer.is_cancelled() {
+                    return Ok(results);
+                }
+
+                let used_by = component
+                    .version
+                    .clone()
+                    .and_then(|version| usage.in_use.get(&(component.component, version)))
+                    .cloned()
+                    .unwrap_or_default();
+                let ruled_out = REFERENCED_COMPONENTS.contains(&component.component)
+                    && !usage.unknown.contains(component.component);
+                let size = get_candidate_size(&component.path, context.observer);
+
+                results.push(ScanResult {
+                    can_delete: ruled_out && used_by.is_empty(),
+                    path_source: root.source.clone(),
+                    component: component.component.to_string(),
+                    version: component.version,
+                    used_by,
+                    ..ScanResult::from_dir_size(&component.path, self.id(), self.owner(), size)
+                });
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn describe(&self) -> CacheCategory {
+        self.base.describe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_literals_and_elvis_defaults() {
+        let gradle = r#"
+buildscript {
+    ext {
+        buildToolsVersion = findProperty('android.buildToolsVersion') ?: '35.0.0'
+        compileSdkVersion = Integer.parseInt(findProperty('android.compileSdkVersion') ?: '34')
+        ndkVersion = "26.1.10909125"
+        minSdkVersion = 23
+    }
+}
+android {
+    compileSdkVersion rootProject.ext.compileSdkVersion
+    compileSdk = 33
+    compileSdkPreview = "UpsideDownCake"
+    compileSdkVersion 'android-31'
+}
+"#;
+
+        assert_eq!(
+            parse_gradle_sdk_references(gradle, &HashMap::new()),
+            SdkReferences {
+                compile_sdk: vec!["34".into(), "33".into(), "31".into()],
+                build_tools: vec!["35.0.0".into()],
+                ndk: vec!["26.1.10909125".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn gradle_properties_override_elvis_defaults() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("app");
+        std::fs::create_dir_all(project.join("android")).unwrap();
+        std::fs::write(
+            project.join("android/build.gradle"),
+            r#"
+ext {
+    buildToolsVersion = findProperty('android.buildToolsVersion') ?: '34.0.0'
+    compileSdkVersion = Integer.parseInt(findProperty('android.compileSdkVersion') ?: '34')
+    ndkVersion = findProperty("android.ndkVersion") ?: "26.1.10909125"
+}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            project.join("android/gradle.properties"),
+            "# expo-build-properties\nandroid.compileSdkVersion=35\nandroid.buildToolsVersion=35.0.0\n",
+        )
+        .unwrap();
+
+        let usage = sdk_components_in_use(std::slice::from_ref(&project));
+        let in_use =
+            |component, version: &str| usage.in_use.contains_key(&(component, version.to_string()));
+        assert!(in_use("platform", "35") && !in_use("platform", "34"));
+        assert!(in_use("build_tools", "35.0.0") && !in_use("build_tools", "34.0.0"));
+        assert!(in_use("ndk", "26.1.10909125"));
+        assert!(usage.unknown.is_empty());
+    }
+
+    #[test]
+    fn unreferenced_components_are_unknown() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("app");
+        std::fs::create_dir_all(project.join("android")).unwrap();
+        std::fs::write(
+            project.join("android/build.gradle"),
+            "compileSdkVersion = 34\n",
+        )
+        .unwrap();
+
+        let usage = sdk_components_in_use(std::slice::from_ref(&project));
+        assert!(usage.in_use.contains_key(&("platform", "34".to_string())));
+        assert_eq!(usage.unknown, HashSet::from(["build_tools", "ndk"]),);
+
+        let usage = sdk_components_in_use(&[]);
+        assert_eq!(
+            usage.unknown,
+            HashSet::from(["platform", "build_tools", "ndk"])
+        );
+    }
+}
@@ -0,0 +1,391 @@
+use crate::models::{CacheCategory, RiskLevel, ScanResult};
+use crate::registry::ScanContext;
+use crate::utils::{expand_glob, get_candidate_size, ToolQuery};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Any,
+    MacOs,
+    Windows,
+    Linux,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else if cfg!(target_os = "windows") {
+            Platform::Windows
+        } else {
+            Platform::Linux
+        }
+    }
+
+    pub fn is_current(self) -> bool {
+        self == Platform::Any || self == Platform::current()
+    }
+}
+
+/// Directory a [`Location`] path is relative to.
+///
+/// The `Xdg*` bases follow the XDG base directory spec: the variable wins
+/// when it holds an absolute path, otherwise the default under home is used.
+#[derive(Debug, Clone, Copy)]
+pub enum Base {
+    Home,
+    Temp,
+    Absolute,
+    XdgCache,
+    XdgConfig,
+    XdgData,
+    /// The directory named by a tool's environment variable, e.g.
+    /// `GRADLE_USER_HOME`. Skipped while the variable is unset.
+    Env(&'static str),
+    /// The directory the tool itself reports. Skipped when it does not answer.
+    Tool(&'static ToolQuery),
+}
+
+/// A candidate cache location, only resolved on the platform it belongs to
+/// and while none of the variables in `overridden_by` is set.
+#[derive(Debug, Clone, Copy)]
+pub struct Location {
+    pub platform: Platform,
+    pub base: Base,
+    pub path: &'static str,
+    pub overridden_by: &'static [&'static str],
+}
+
+/// A resolved location and where its base directory came from: `"default"`,
+/// the environment variable that named it or the command that reported it.
+#[derive(Debug, Clone)]
+pub struct CandidatePath {
+    pub path: PathBuf,
+    pub source: String,
+}
+
+impl Location {
+    pub const fn home(platform: Platform, path: &'static str) -> Self {
+        Location {
+            platform,
+            base: Base::Home,
+            path,
+            overridden_by: &[],
+        }
+    }
+
+    pub const fn temp(platform: Platform, path: &'static str) -> Self {
+        Location {
+            platform,
+            base: Base::Temp,
+            path,
+            overridden_by: &[],
+        }
+    }
+
+    pub const fn absolute(platform: Platform, path: &'static str) -> Self {
+        Location {
+            platform,
+            base: Base::Absolute,
+            path,
+            overridden_by: &[],
+        }
+    }
+
+    pub const fn xdg_cache(path: &'static str) -> Self {
+        Location {
+            platform: Platform::Linux,
+            base: Base::XdgCache,
+            path,
+            overridden_by: &[],
+        }
+    }
+
+    pub const fn xdg_config(path: &'static str) -> Self {
+        Location {
+            platform: Platform::Linux,
+            base: Base::XdgConfig,
+            path,
+            overridden_by: &[],
+        }
+    }
+
+    pub const fn xdg_data(path: &'static str) -> Self {
+        Location {
+            platform: Platform::Linux,
+            base: Base::XdgData,
+            path,
+            overridden_by: &[],
+        }
+    }
+
+    pub const fn env(var: &'static str, path: &'static str) -> Self {
+        Location {
+            platform: Platform::Any,
+            base: Base::Env(var),
+            path,
+            overridden_by: &[],
+        }
+    }
+
+    pub const fn tool(query: &'static ToolQuery, path: &'static str) -> Self {
+        Location {
+            platform: Platform::Any,
+            base: Base::Tool(query),
+            path,
+            overridden_by: &[],
+        }
+    }
+
+    /// Drops this default location while any of `vars` points the tool
+    /// somewhere else.
+    pub const fn unless_env(self, vars: &'static [&'static str]) -> Self {
+        Location {
+            overridden_by: vars,
+            ..self
+        }
+    }
+
+    pub fn applies(&self) -> bool {
+        let base_available = match self.base {
+            Base::Env(var) => env_dir(var).is_some(),
+            Base::Tool(query) => query.answer().is_some(),
+            _ => true,
+        };
+
+        self.platform.is_current()
+            && base_available
+            && !self.overridden_by.iter().any(|var| env_dir(var).is_some())
+    }
+
+    pub fn source(&self) -> String {
+        if let Base::Tool(query) = self.base {
+            return query.command_line();
+        }
+
+        let var = match self.base {
+            Base::Env(var) => Some(var),
+            Base::XdgCache => Some("XDG_CACHE_HOME").filter(|var| xdg_var(var).is_some()),
+            Base::XdgConfig => Some("XDG_CONFIG_HOME").filter(|var| xdg_var(var).is_some()),
+            Base::XdgData => Some("XDG_DATA_HOME").filter(|var| xdg_var(var).is_some()),
+            Base::Home | Base::Temp | Base::Absolute | Base::Tool(_) => None,
+        };
+
+        var.map_or_else(|| "default".to_string(), |var| format!("${var}"))
+    }
+
+    fn base_dir(&self) -> Result<PathBuf, String> {
+        match self.base {
+            Base::Home => home_dir(),
+            Base::Temp => Ok(std::env::temp_dir()),
+            Base::Absolute => Ok(PathBuf::new()),
+            Base::XdgCache => xdg_dir("XDG_CACHE_HOME", ".cache"),
+            Base::XdgConfig => xdg_dir("XDG_CONFIG_HOME", ".config"),
+            Base::XdgData => xdg_dir("XDG_DATA_HOME", ".local/share"),
+            Base::Env(var) => env_dir(var).ok_or_else(|| format!("{var} is not set")),
+            Base::Tool(query) => query
+                .answer()
+                .ok_or_else(|| format!("`{}` gave no answer", query.command_line())),
+        }
+    }
+
+    /// The location as declared, with any glob pattern left unexpanded.
+    pub fn pattern_path(&self) -> Result<PathBuf, String> {
+        let base_dir = self.base_dir()?;
+        if self.path.is_empty() {
+            return Ok(base_dir);
+        }
+
+        Ok(base_dir.join(self.path))
+    }
+
+    /// Every path this location refers to, expanding glob patterns.
+    pub fn resolve(&self) -> Result<Vec<PathBuf>, String> {
+        expand_glob(&self.base_dir()?, self.path)
+    }
+}
+
+fn home_dir() -> Result<PathBuf, String> {
+    Ok(dirs::home_dir().ok_or("Could not find home directory")?)
+}
+
+fn env_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+fn xdg_var(var: &str) -> Option<PathBuf> {
+    env_dir(var).filter(|dir| dir.is_absolute())
+}
+
+fn xdg_dir(var: &str, default: &str) -> Result<PathBuf, String> {
+    match xdg_var(var) {
+        Some(dir) => Ok(dir),
+        None => Ok(home_dir()?.join(default)),
+    }
+}
+
+/// Whether one path contains the other, directly or through symlinks.
+pub fn overlaps(a: &Path, b: &Path) -> bool {
+    let nested = |a: &Path, b: &Path| a.starts_with(b) || b.starts_with(a);
+    if nested(a, b) {
+        return true;
+    }
+
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => nested(&a, &b),
+        _ => false,
+    }
+}
+
+/// A category of regenerable files the cleaner knows how to find.
+///
+/// Most categories only need to declare their static data and use the
+/// default `scan`; categories with a different layout wrap a
+/// [`CacheDefinition`] and override it.
+pub trait CacheTarget: Sync {
+    fn base(&self) -> &CacheDefinition;
+
+    fn id(&self) -> &'static str {
+        self.base().id
+    }
+
+    fn name(&self) -> &'static str {
+        self.base().name
+    }
+
+    fn description(&self) -> &'static str {
+        self.base().description
+    }
+
+    fn risk(&self) -> RiskLevel {
+        self.base().risk
+    }
+
+    /// The tool that creates the cached data, shown next to each result.
+    fn owner(&self) -> &'static str {
+        self.base().owner
+    }
+
+    fn locations(&self) -> &'static [Location] {
+        self.base().locations
+    }
+
+    /// Every path the applicable locations resolve to, in declaration order
+    /// so environment overrides and tool answers listed first win over later
+    /// duplicates. A path overlapping an earlier one, the same directory or
+    /// one nested in the other, is skipped so nothing is counted twice.
+    fn candidates(&self) -> Result<Vec<CandidatePath>, String> {
+        let mut candidates: Vec<CandidatePath> = Vec::new();
+
+        for location in self.locations().iter().filter(|l| l.applies()) {
+            let source = location.source();
+            for path in location.resolve()? {
+                if !candidates
+                    .iter()
+                    .any(|candidate| overlaps(&candidate.path, &path))
+                {
+                    candidates.push(CandidatePath {
+                        path,
+                        source: source.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    /// The folders the cache watcher follows: the locations, plus the folders
+    /// a breakdown enumerates, so entries created in them later are noticed.
+    /// They need not exist yet.
+    fn watch_roots(&self) -> Vec<CandidatePath> {
+        self.base().candidates().unwrap_or_default()
+    }
+
+    fn scan(&self, context: &ScanContext) -> Result<Vec<ScanResult>, String> {
+        let mut results = Vec::new();
+
+        for candidate in self.candidates()? {
+            if candidate.path.exists() {
+                let size = get_candidate_size(&candidate.path, context.observer);
+                results.push(ScanResult {
+                    path_source: candidate.source,
+                    ..ScanResult::from_dir_size(&candidate.path, self.id(), self.owner(), size)
+                });
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn describe(&self) -> CacheCategory {
+        CacheCategory {
+            id: self.id().to_string(),
+            name: self.name().to_string(),
+            description: self.description().to_string(),
+            risk: self.risk(),
+            owner: self.owner().to_string(),
+            locations: self
+                .locations()
+                .iter()
+                .filter(|location| location.applies())
+                .filter_map(|location| location.pattern_path().ok())
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+        }
+    }
+}
+
+/// A category fully described by static data.
+pub struct CacheDefinition {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub risk: RiskLevel,
+    pub owner: &'static str,
+    pub locations: &'static [Location],
+}
+
+impl CacheTarget for CacheDefinition {
+    fn base(&self) -> &CacheDefinition {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TestEnv;
+
+    // `dirs` reads the home folder from $HOME on Unix only.
+    #[cfg(unix)]
+    #[test]
+    fn absolute_xdg_vars_win_and_relative_ones_are_ignored() {
+        let mut env = TestEnv::lock();
+        env.set("HOME", "/home/dev");
+
+        env.set("XDG_CACHE_HOME", "/data/cache");
+        assert_eq!(
+            xdg_dir("XDG_CACHE_HOME", ".cache"),
+            Ok(PathBuf::from("/data/cache"))
+        );
+        assert_eq!(Location::xdg_cache("yarn").source(), "$XDG_CACHE_HOME");
+
+        for ignored in ["relative/cache", ""] {
+            env.set("XDG_CACHE_HOME", ignored);
+            assert_eq!(
+                xdg_dir("XDG_CACHE_HOME", ".cache"),
+                Ok(PathBuf::from("/home/dev/.cache"))
+            );
+            assert_eq!(Location::xdg_cache("yarn").source(), "default");
+        }
+
+        env.remove("XDG_CACHE_HOME");
+        assert_eq!(
+            xdg_dir("XDG_CACHE_HOME", ".cache"),
+            Ok(PathBuf::from("/home/dev/.cache"))
+        );
+    }
+}
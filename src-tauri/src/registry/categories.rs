@@ -0,0 +1,600 @@
+use crate::models::RiskLevel;
+use crate::registry::{
+    AndroidCache, AndroidSdk, CacheDefinition, CacheTarget, CocoaPodsCache, IosCache, Location,
+    Platform,
+};
+use crate::utils::{parse_path_output, ToolQuery};
+
+const NPM_CACHE_QUERY: ToolQuery = ToolQuery {
+    program: "npm",
+    args: &["config", "get", "cache"],
+    parse: parse_path_output,
+};
+
+/// Yarn 1 answers `cache dir`; Yarn 2+ only knows `cacheFolder`.
+const YARN_CACHE_DIR_QUERY: ToolQuery = ToolQuery {
+    program: "yarn",
+    args: &["cache", "dir"],
+    parse: parse_path_output,
+};
+
+const YARN_CACHE_FOLDER_QUERY: ToolQuery = ToolQuery {
+    program: "yarn",
+    args: &["config", "get", "cacheFolder"],
+    parse: parse_path_output,
+};
+
+const PNPM_STORE_QUERY: ToolQuery = ToolQuery {
+    program: "pnpm",
+    args: &["store", "path"],
+    parse: parse_path_output,
+};
+
+const PIP_CACHE_QUERY: ToolQuery = ToolQuery {
+    program: "pip",
+    args: &["cache", "dir"],
+    parse: parse_path_output,
+};
+
+const EXPO_CACHE: CacheDefinition = CacheDefinition {
+    id: "expo_cache",
+    name: "Expo Cache",
+    description: "Clean Expo development cache and temporary files",
+    risk: RiskLevel::Safe,
+    owner: "Expo",
+    locations: &[
+        Location::home(Platform::Any, ".expo"),
+        Location::home(Platform::MacOs, "Library/Caches/Expo"),
+        Location::home(Platform::Windows, "AppData/Local/Expo"),
+        Location::xdg_cache("expo"),
+    ],
+};
+
+const METRO_CACHE: CacheDefinition = CacheDefinition {
+    id: "metro_cache",
+    name: "Metro Cache",
+    description: "Clean Metro bundler cache files",
+    risk: RiskLevel::Safe,
+    owner: "Metro",
+    locations: &[
+        Location::home(Platform::Any, ".metro"),
+        Location::home(Platform::MacOs, "Library/Caches/Metro"),
+        Location::home(Platform::Windows, "AppData/Local/Metro"),
+        Location::temp(Platform::Any, "metro-cache"),
+        Location::temp(Platform::Any, "react-native-packager-cache"),
+        Location::xdg_cache("metro"),
+    ],
+};
+
+const IOS_CACHE: CacheDefinition = CacheDefinition {
+    id: "ios_cache",
+    name: "iOS Build Cache",
+    description: "Clean iOS simulator and build artifacts",
+    risk: RiskLevel::Low,
+    owner: "Xcode",
+    locations: &[
+        Location::home(Platform::MacOs, "Library/Caches/com.apple.dt.Xcode"),
+        Location::home(Platform::MacOs, "Library/Developer/CoreSimulator/Caches"),
+        Location::home(Platform::MacOs, "Library/Logs/CoreSimulator"),
+    ],
+};
+
+/// Either variable names the Android SDK; `ANDROID_HOME` is the older one.
+const ANDROID_SDK_VARS: &[&str] = &["ANDROID_SDK_ROOT", "ANDROID_HOME"];
+
+const ANDROID_CACHE: CacheDefinition = CacheDefinition {
+    id: "android_cache",
+    name: "Android Cache",
+    description: "Clean Android build cache and temporary files",
+    risk: RiskLevel::Low,
+    owner: "Gradle / Android SDK",
+    locations: &[
+        Location::env("ANDROID_SDK_ROOT", ".temp"),
+        Location::env("ANDROID_HOME", ".temp"),
+        Location::home(Platform::Any, ".android/cache"),
+        Location::home(Platform::Any, ".android/avd/.temp"),
+        Location::home(Platform::MacOs, "Library/Android/sdk/.temp").unless_env(ANDROID_SDK_VARS),
+        Location::home(Platform::Windows, "AppData/Local/Android/Sdk/.temp")
+            .unless_env(ANDROID_SDK_VARS),
+        Location::home(Platform::Windows, "AppData/Local/Temp/AndroidEmulator"),
+        Location::home(Platform::Linux, "Android/Sdk/.temp").unless_env(ANDROID_SDK_VARS),
+    ],
+};
+
+/// The SDK roots themselves; [`AndroidSdk`] breaks them down into components.
+const ANDROID_SDK: CacheDefinition = CacheDefinition {
+    id: "android_sdk",
+    name: "Android SDK Components",
+    description: "Remove SDK packages no project builds against",
+    risk: RiskLevel::Medium,
+    owner: "Android SDK",
+    locations: &[
+        Location::env("ANDROID_SDK_ROOT", ""),
+        Location::env("ANDROID_HOME", ""),
+        Location::home(Platform::MacOs, "Library/Android/sdk").unless_env(ANDROID_SDK_VARS),
+        Location::home(Platform::Windows, "AppData/Local/Android/Sdk").unless_env(ANDROID_SDK_VARS),
+        Location::home(Platform::Linux, "Android/Sdk").unless_env(ANDROID_SDK_VARS),
+    ],
+};
+
+const NPM_CACHE: CacheDefinition = CacheDefinition {
+    id: "npm_cache",
+    name: "NPM Cache",
+    description: "Clean Node.js package manager cache",
+    risk: RiskLevel::Safe,
+    owner: "npm / Yarn",
+    locations: &[
+        Location::env("npm_config_cache", "_cacache"),
+        Location::env("YARN_CACHE_FOLDER", ""),
+        Location::tool(&NPM_CACHE_QUERY, "_cacache"),
+        Location::tool(&YARN_CACHE_DIR_QUERY, ""),
+        Location::tool(&YARN_CACHE_FOLDER_QUERY, ""),
+        Location::home(Platform::Any, ".npm/_cacache").unless_env(&["npm_config_cache"]),
+        Location::home(Platform::Any, ".yarn/cache"),
+        Location::home(Platform::MacOs, "Library/Caches/npm"),
+        Location::home(Platform::MacOs, "Library/Caches/yarn").unless_env(&["YARN_CACHE_FOLDER"]),
+        Location::home(Platform::Windows, "AppData/Roaming/npm-cache")
+            .unless_env(&["npm_config_cache"]),
+        Location::home(Platform::Windows, "AppData/Local/Yarn/Cache")
+            .unless_env(&["YARN_CACHE_FOLDER"]),
+        Location::xdg_cache("yarn").unless_env(&["YARN_CACHE_FOLDER"]),
+    ],
+};
+
+const WATCHMAN_CACHE: CacheDefinition = CacheDefinition {
+    id: "watchman_cache",
+    name: "Watchman Logs",
+    description: "Clean Watchman file watching service logs",
+    risk: RiskLevel::Low,
+    owner: "Watchman",
+    locations: &[
+        Location::home(Platform::Any, ".watchman"),
+        Location::temp(Platform::Any, "watchman"),
+        Location::home(Platform::MacOs, "Library/Logs/watchman"),
+    ],
+};
+
+const COCOAPODS_CACHE: CacheDefinition = CacheDefinition {
+    id: "cocoapods_cache",
+    name: "CocoaPods Cache",
+    description: "Clean CocoaPods dependency cache",
+    risk: RiskLevel::Low,
+    owner: "CocoaPods",
+    locations: &[
+        Location::env("CP_HOME_DIR", "repos"),
+        Location::home(Platform::Any, ".cocoapods/repos").unless_env(&["CP_HOME_DIR"]),
+    ],
+};
+
+const FLIPPER_LOGS: CacheDefinition = CacheDefinition {
+    id: "flipper_logs",
+    name: "Flipper Logs",
+    description: "Clean Flipper debugging tool logs",
+    risk: RiskLevel::Safe,
+    owner: "Flipper",
+    locations: &[
+        Location::home(Platform::Any, ".flipper"),
+        Location::home(Platform::MacOs, "Library/Application Support/flipper"),
+        Location::home(Platform::Windows, "AppData/Roaming/flipper"),
+        Location::xdg_config("flipper"),
+    ],
+};
+
+const REACT_NATIVE_CACHE: CacheDefinition = CacheDefinition {
+    id: "react_native_cache",
+    name: "React Native Cache",
+    description: "Clean React Native CLI cache and development files",
+    risk: RiskLevel::Safe,
+    owner: "React Native CLI",
+    locations: &[
+        Location::home(Platform::Any, ".react-native"),
+        Location::home(Platform::MacOs, "Library/Caches/com.facebook.react"),
+        Location::home(Platform::Windows, "AppData/Local/React Native"),
+    ],
+};
+
+const HERMES_CACHE: CacheDefinition = CacheDefinition {
+    id: "hermes_cache",
+    name: "Hermes Cache",
+    description: "Clean Hermes JavaScript engine cache",
+    risk: RiskLevel::Safe,
+    owner: "Hermes",
+    locations: &[
+        Location::home(Platform::Any, ".hermes"),
+        Location::home(Platform::MacOs, "Library/Caches/Hermes"),
+        Location::home(Platform::Windows, "AppData/Local/Hermes"),
+        Location::temp(Platform::Any, "hermes-*"),
+    ],
+};
+
+const VSCODE_CACHE: CacheDefinition = CacheDefinition {
+    id: "vscode_cache",
+    name: "VS Code Cache",
+    description: "Clean Visual Studio Code logs and extensions",
+    risk: RiskLevel::Medium,
+    owner: "VS Code",
+    locations: &[
+        Location::home(Platform::Any, ".vscode/extensions"),
+        Location::home(Platform::MacOs, "Library/Application Support/Code/logs"),
+        Location::home(Platform::MacOs, "Library/Caches/com.microsoft.VSCode"),
+        Location::home(Platform::Windows, "AppData/Roaming/Code/logs"),
+        Location::home(Platform::Windows, "AppData/Roaming/Code/CachedExtensions"),
+        Location::xdg_config("Code/logs"),
+        Location::xdg_config("Code/Cache"),
+        Location::xdg_config("Code/CachedData"),
+        Location::xdg_config("Code/CachedExtensionVSIXs"),
+    ],
+};
+
+const ANDROID_STUDIO_CACHE: CacheDefinition = CacheDefinition {
+    id: "android_studio_cache",
+    name: "Android Studio Cache",
+    description: "Clean Android Studio system cache and logs",
+    risk: RiskLevel::Low,
+    owner: "Android Studio",
+    locations: &[
+        Location::home(
+            Platform::MacOs,
+            "Library/Application Support/Google/AndroidStudio*/system",
+        ),
+        Location::home(Platform::MacOs, "Library/Logs/Google/AndroidStudio*"),
+        Location::home(Platform::MacOs, "Library/Caches/Google/AndroidStudio*"),
+        Location::home(
+            Platform::Windows,
+            "AppData/Local/Google/AndroidStudio*/system",
+        ),
+        Location::home(Platform::Windows, "AppData/Local/Google/AndroidStudio*/log"),
+        Location::xdg_cache("Google/AndroidStudio*"),
+    ],
+};
+
+const HOMEBREW_CACHE: CacheDefinition = CacheDefinition {
+    id: "homebrew_cache",
+    name: "Homebrew Cache",
+    description: "Clean Homebrew package manager cache",
+    risk: RiskLevel::Safe,
+    owner: "Homebrew",
+    locations: &[
+        Location::absolute(Platform::MacOs, "/opt/homebrew/var/cache"),
+        Location::absolute(Platform::MacOs, "/usr/local/var/cache"),
+        Location::home(Platform::MacOs, "Library/Caches/Homebrew"),
+        Location::xdg_cache("Homebrew"),
+    ],
+};
+
+const GIT_CACHE: CacheDefinition = CacheDefinition {
+    id: "git_cache",
+    name: "Git Client Cache",
+    description: "Clean GitHub Desktop cache and logs",
+    risk: RiskLevel::Safe,
+    owner: "GitHub Desktop",
+    locations: &[
+        Location::home(Platform::MacOs, "Library/Caches/com.github.GitHubDesktop"),
+        Location::home(Platform::Windows, "AppData/Roaming/GitHub Desktop/logs"),
+        Location::xdg_config("GitHub Desktop/logs"),
+    ],
+};
+
+const INTELLIJ_CACHE: CacheDefinition = CacheDefinition {
+    id: "intellij_cache",
+    name: "JetBrains Cache",
+    description: "Clean JetBrains IDE caches and logs",
+    risk: RiskLevel::Low,
+    owner: "JetBrains IDEs",
+    locations: &[
+        Location::home(Platform::MacOs, "Library/Caches/JetBrains"),
+        Location::home(Platform::MacOs, "Library/Logs/JetBrains"),
+        Location::home(Platform::Windows, "AppData/Local/JetBrains"),
+        Location::xdg_cache("JetBrains"),
+    ],
+};
+
+const PYTHON_CACHE: CacheDefinition = CacheDefinition {
+    id: "python_cache",
+    name: "Python Cache",
+    description: "Clean pip, Poetry and Conda package caches",
+    risk: RiskLevel::Safe,
+    owner: "pip / Poetry / Conda",
+    locations: &[
+        Location::env("PIP_CACHE_DIR", ""),
+        Location::tool(&PIP_CACHE_QUERY, ""),
+        Location::home(Platform::Any, ".pip/cache").unless_env(&["PIP_CACHE_DIR"]),
+        Location::home(Platform::MacOs, "Library/Caches/pip").unless_env(&["PIP_CACHE_DIR"]),
+        Location::home(Platform::Windows, "AppData/Local/pip/Cache").unless_env(&["PIP_CACHE_DIR"]),
+        Location::env("POETRY_CACHE_DIR", ""),
+        Location::xdg_cache("pypoetry").unless_env(&["POETRY_CACHE_DIR"]),
+        Location::home(Platform::MacOs, "Library/Caches/pypoetry")
+            .unless_env(&["POETRY_CACHE_DIR"]),
+        Location::home(Platform::Windows, "AppData/Local/pypoetry/Cache")
+            .unless_env(&["POETRY_CACHE_DIR"]),
+        Location::home(Platform::Any, ".conda/pkgs"),
+        Location::home(Platform::MacOs, "Library/Caches/conda"),
+        Location::xdg_cache("pip").unless_env(&["PIP_CACHE_DIR"]),
+    ],
+};
+
+const RUST_CACHE: CacheDefinition = CacheDefinition {
+    id: "rust_cache",
+    name: "Rust Cache",
+    description: "Clean Cargo registry and rustup download caches",
+    risk: RiskLevel::Safe,
+    owner: "Cargo / rustup",
+    locations: &[
+        Location::env("CARGO_HOME", "registry"),
+        Location::env("CARGO_HOME", "git"),
+        Location::env("RUSTUP_HOME", "downloads"),
+        Location::env("RUSTUP_HOME", "tmp"),
+        Location::home(Platform::Any, ".cargo/registry").unless_env(&["CARGO_HOME"]),
+        Location::home(Platform::Any, ".cargo/git").unless_env(&["CARGO_HOME"]),
+        Location::home(Platform::Any, ".rustup/downloads").unless_env(&["RUSTUP_HOME"]),
+        Location::home(Platform::Any, ".rustup/tmp").unless_env(&["RUSTUP_HOME"]),
+    ],
+};
+
+const BROWSER_CACHE: CacheDefinition = CacheDefinition {
+    id: "browser_cache",
+    name: "Browser Cache",
+    description: "Clean Chrome, Firefox, Safari and Edge caches",
+    risk: RiskLevel::Low,
+    owner: "Web browsers",
+    locations: &[
+        Location::home(Platform::MacOs, "Library/Caches/Google/Chrome"),
+        Location::home(Platform::MacOs, "Library/Caches/Mozilla/Firefox"),
+        Location::home(Platform::MacOs, "Library/Caches/com.apple.Safari"),
+        Location::home(
+            Platform::Windows,
+            "AppData/Local/Google/Chrome/User Data/Default/Cache",
+        ),
+        Location::home(Platform::Windows, "AppData/Local/Mozilla/Firefox/Profiles"),
+        Location::home(
+            Platform::Windows,
+            "AppData/Local/Microsoft/Edge/User Data/Default/Cache",
+        ),
+        Location::xdg_cache("google-chrome"),
+        Location::xdg_cache("chromium"),
+        Location::xdg_cache("microsoft-edge"),
+        Location::xdg_cache("mozilla/firefox"),
+        Location::home(Platform::Linux, ".mozilla/firefox/*/cache2"),
+    ],
+};
+
+const SYSTEM_LOGS: CacheDefinition = CacheDefinition {
+    id: "system_logs",
+    name: "System Logs",
+    description: "Clean user logs and crash reports",
+    risk: RiskLevel::High,
+    owner: "System",
+    // Not /tmp: temp_files, metro_cache, hermes_cache and watchman_cache
+    // already cover what is in it.
+    locations: &[
+        Location::home(Platform::MacOs, "Library/Logs"),
+        Location::home(Platform::MacOs, "Library/Application Support/CrashReporter"),
+    ],
+};
+
+const PNPM_CACHE: CacheDefinition = CacheDefinition {
+    id: "pnpm_cache",
+    name: "PNPM Store",
+    description: "Clean the pnpm content-addressable store",
+    risk: RiskLevel::Low,
+    owner: "pnpm",
+    locations: &[
+        Location::env("PNPM_HOME", "store"),
+        Location::tool(&PNPM_STORE_QUERY, ""),
+        Location::home(Platform::Any, ".pnpm-store"),
+        Location::home(Platform::MacOs, "Library/pnpm").unless_env(&["PNPM_HOME"]),
+        Location::home(Platform::Windows, "AppData/Local/pnpm-cache"),
+        Location::xdg_data("pnpm/store").unless_env(&["PNPM_HOME"]),
+        Location::xdg_cache("pnpm"),
+    ],
+};
+
+const UNITY_CACHE: CacheDefinition = CacheDefinition {
+    id: "unity_cache",
+    name: "Unity Cache",
+    description: "Clean Unity asset cache and logs",
+    risk: RiskLevel::Low,
+    owner: "Unity",
+    locations: &[
+        Location::home(Platform::MacOs, "Library/Unity/cache"),
+        Location::home(Platform::MacOs, "Library/Logs/Unity"),
+        Location::home(Platform::Windows, "AppData/Local/Unity/cache"),
+        Location::home(Platform::Windows, "AppData/LocalLow/Unity"),
+        Location::xdg_cache("unity3d"),
+        Location::xdg_config("unity3d/Editor.log"),
+    ],
+};
+
+const SIMULATOR_CACHE: CacheDefinition = CacheDefinition {
+    id: "simulator_cache",
+    name: "iOS Simulators",
+    description: "Clean iOS simulator devices, logs and saved state",
+    risk: RiskLevel::High,
+    owner: "iOS Simulator",
+    locations: &[
+        Location::home(Platform::MacOs, "Library/Developer/CoreSimulator/Devices"),
+        Location::home(Platform::MacOs, "Library/Logs/CoreSimulator"),
+        Location::home(
+            Platform::MacOs,
+            "Library/Saved Application State/com.apple.iphonesimulator.savedState",
+        ),
+    ],
+};
+
+const TEMP_FILES: CacheDefinition = CacheDefinition {
+    id: "temp_files",
+    name: "Temp Files",
+    description: "Clean system temporary files",
+    risk: RiskLevel::Safe,
+    owner: "React Native toolchain",
+    locations: &[
+        Location::temp(Platform::Any, "react-native-*"),
+        Location::temp(Platform::Any, "metro-*"),
+        Location::temp(Platform::Any, "expo-*"),
+        Location::temp(Platform::Any, "haste-map-*"),
+        Location::home(Platform::Any, ".tmp"),
+    ],
+};
+
+const BUILD_ARTIFACTS: CacheDefinition = CacheDefinition {
+    id: "build_artifacts",
+    name: "Build Artifacts",
+    description: "Clean old APK and IPA files from common folders",
+    risk: RiskLevel::Medium,
+    owner: "Build output",
+    locations: &[
+        Location::home(Platform::Any, "Desktop/*.apk"),
+        Location::home(Platform::Any, "Desktop/*.ipa"),
+        Location::home(Platform::Any, "Downloads/*.apk"),
+        Location::home(Platform::Any, "Downloads/*.ipa"),
+        Location::home(Platform::Any, "Documents/*.apk"),
+        Location::home(Platform::Any, "Documents/*.ipa"),
+    ],
+};
+
+/// Every category the cleaner can scan, in dashboard order.
+pub static CACHE_TARGETS: &[&dyn CacheTarget] = &[
+    &EXPO_CACHE,
+    &METRO_CACHE,
+    &IosCache { base: IOS_CACHE },
+    &AndroidCache {
+        base: ANDROID_CACHE,
+    },
+    &AndroidSdk { base: ANDROID_SDK },
+    &NPM_CACHE,
+    &WATCHMAN_CACHE,
+    &CocoaPodsCache {
+        base: COCOAPODS_CACHE,
+    },
+    &FLIPPER_LOGS,
+    &TEMP_FILES,
+    &REACT_NATIVE_CACHE,
+    &HERMES_CACHE,
+    &VSCODE_CACHE,
+    &ANDROID_STUDIO_CACHE,
+    &BUILD_ARTIFACTS,
+    &HOMEBREW_CACHE,
+    &GIT_CACHE,
+    &INTELLIJ_CACHE,
+    &PYTHON_CACHE,
+    &RUST_CACHE,
+    &BROWSER_CACHE,
+    &SYSTEM_LOGS,
+    &PNPM_CACHE,
+    &UNITY_CACHE,
+    &SIMULATOR_CACHE,
+];
+
+pub fn find_target(id: &str) -> Option<&'static dyn CacheTarget> {
+    CACHE_TARGETS
+        .iter()
+        .copied()
+        .find(|target| target.id() == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{gradle_user_home, Base};
+    use crate::utils::TestEnv;
+    use std::path::PathBuf;
+
+    fn candidates(target: &CacheDefinition) -> Vec<(PathBuf, String)> {
+        target
+            .candidates()
+            .unwrap()
+            .into_iter()
+            .map(|candidate| (candidate.path, candidate.source))
+            .collect()
+    }
+
+    #[test]
+    fn poetry_has_a_default_cache_on_every_platform() {
+        for platform in [Platform::MacOs, Platform::Windows, Platform::Linux] {
+            assert!(
+                PYTHON_CACHE.locations.iter().any(|location| {
+                    location.platform == platform
+                        && location.path.contains("pypoetry")
+                        && location.overridden_by == ["POETRY_CACHE_DIR"]
+                }),
+                "no Poetry cache for {platform:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn no_category_covers_the_whole_temp_dir() {
+        for target in CACHE_TARGETS {
+            for location in target.locations() {
+                let whole_temp = match location.base {
+                    Base::Temp => location.path.is_empty(),
+                    Base::Absolute => ["/tmp", "/var/tmp"].contains(&location.path),
+                    _ => false,
+                };
+                assert!(!whole_temp, "{} lists the temp dir", target.id());
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn gradle_user_home_replaces_the_default() {
+        let mut env = TestEnv::lock();
+        env.set("HOME", "/home/dev");
+
+        env.set("GRADLE_USER_HOME", "/data/gradle");
+        let home = gradle_user_home().unwrap();
+        assert_eq!(home.path, PathBuf::from("/data/gradle"));
+        assert_eq!(home.source, "$GRADLE_USER_HOME");
+
+        env.remove("GRADLE_USER_HOME");
+        let home = gradle_user_home().unwrap();
+        assert_eq!(home.path, PathBuf::from("/home/dev/.gradle"));
+        assert_eq!(home.source, "default");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn either_sdk_variable_replaces_the_default_sdk() {
+        let mut env = TestEnv::lock();
+        env.set("HOME", "/home/dev");
+        env.remove("ANDROID_SDK_ROOT");
+        env.remove("ANDROID_HOME");
+        assert_eq!(
+            candidates(&ANDROID_SDK),
+            [(
+                PathBuf::from("/home/dev/Android/Sdk"),
+                "default".to_string()
+            )]
+        );
+
+        env.set("ANDROID_HOME", "/opt/android");
+        assert_eq!(
+            candidates(&ANDROID_SDK),
+            [(PathBuf::from("/opt/android"), "$ANDROID_HOME".to_string())]
+        );
+
+        // Both set to one SDK: listed once, under the newer variable.
+        env.set("ANDROID_SDK_ROOT", "/opt/android");
+        assert_eq!(
+            candidates(&ANDROID_SDK),
+            [(
+                PathBuf::from("/opt/android"),
+                "$ANDROID_SDK_ROOT".to_string()
+            )]
+        );
+
+        env.set("ANDROID_SDK_ROOT", "/opt/sdk");
+        assert_eq!(
+            candidates(&ANDROID_SDK),
+            [
+                (PathBuf::from("/opt/sdk"), "$ANDROID_SDK_ROOT".to_string()),
+                (PathBuf::from("/opt/android"), "$ANDROID_HOME".to_string()),
+            ]
+        );
+        assert!(!ANDROID_CACHE
+            .locations
+            .iter()
+            .any(|location| location.applies() && location.path == "Android/Sdk/.temp"));
+    }
+}
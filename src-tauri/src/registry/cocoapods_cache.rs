@@ -0,0 +1,340 @@
+use crate::models::{CacheCategory, ScanResult};
+use crate::registry::{
+    overlaps, CacheDefinition, CacheTarget, CandidatePath, Location, Platform, ScanContext,
+};
+use crate::utils::{get_candidate_size, parse_pod_cache_list, subdirs, ToolQuery};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const POD_CACHE_QUERY: ToolQuery = ToolQuery {
+    program: "pod",
+    args: &["cache", "list"],
+    parse: parse_pod_cache_list,
+};
+
+/// The download cache `pod install` fills, holding `Pods/Release`,
+/// `Pods/External` and `Pods/Specs`.
+const POD_CACHE_ROOTS: &[Location] = &[
+    Location::tool(&POD_CACHE_QUERY, ""),
+    Location::home(Platform::MacOs, "Library/Caches/CocoaPods"),
+    Location::xdg_cache("CocoaPods"),
+];
+
+/// Where a project keeps its lockfile, relative to the project.
+const PODFILE_LOCKS: &[&str] = &["ios/Podfile.lock", "macos/Podfile.lock", "Podfile.lock"];
+
+/// The CocoaPods category, with the download cache split into one entry per
+/// pod version so versions a project still locks survive a clean.
+pub struct CocoaPodsCache {
+    pub base: CacheDefinition,
+}
+
+/// A pod download or podspec in the cache. External (git) downloads are
+/// keyed by a hash of their source, so they have no version.
+#[derive(Debug, Clone)]
+pub struct PodCacheEntry {
+    pub path: PathBuf,
+    pub component: &'static str,
+    pub pod: String,
+    pub version: Option<String>,
+}
+
+/// The entries in the cache root `root`: `Pods/Release/<pod>/<version>-<hash>`,
+/// `Pods/External/<pod>/<hash>` and `Pods/Specs/Release/<pod>/<version>.podspec.json`.
+pub fn pod_cache_entries(root: &Path) -> Vec<PodCacheEntry> {
+    let pods = root.join("Pods");
+    let mut entries = Vec::new();
+
+    for (pod, pod_dir) in subdirs(&pods.join("Release")) {
+        for (name, path) in subdirs(&pod_dir) {
+            entries.push(PodCacheEntry {
+                path,
+                component: "pod_release",
+                pod: pod.clone(),
+                version: Some(release_version(&name).to_string()),
+            });
+        }
+    }
+
+    for (pod, pod_dir) in subdirs(&pods.join("External")) {
+        for (_, path) in subdirs(&pod_dir) {
+            entries.push(PodCacheEntry {
+                path,
+                component: "pod_external",
+                pod: pod.clone(),
+                version: None,
+            });
+        }
+    }
+
+    for (pod, pod_dir) in subdirs(&pods.join("Specs/Release")) {
+        let Ok(specs) = std::fs::read_dir(&pod_dir) else {
+            continue;
+        };
+        for spec in specs.filter_map(|e| e.ok()) {
+            let name = spec.file_name().to_string_lossy().to_string();
+            if let Some(version) = name.strip_suffix(".podspec.json") {
+                entries.push(PodCacheEntry {
+                    path: spec.path(),
+                    component: "pod_spec",
+                    pod: pod.clone(),
+                    version: Some(version.to_string()),
+                });
+            }
+        }
+    }
+
+    entries
+}
+
+/// `1.83.0-a1b2c` is version 1.83.0 with the first five characters of the
+/// podspec checksum appended.
+fn release_version(name: &str) -> &str {
+    match name.rsplit_once('-') {
+        Some((version, checksum))
+            if checksum.len() == 5 && checksum.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            version
+        }
+        _ => name,
+    }
+}
+
+/// The `(pod, version)` pairs in the `PODS` section of a `Podfile.lock`.
+/// Subspecs such as `RCT-Folly/Default` are reported by their root pod,
+/// which is what the cache is organized by.
+pub fn parse_podfile_lock(lockfile: &str) -> Vec<(String, String)> {
+    let mut pods = Vec::new();
+
+    let section = lockfile
+        .lines()
+        .skip_while(|line| line.trim_end() != "PODS:")
+        .skip(1)
+        .take_while(|line| line.is_empty() || line.starts_with(' '));
+
+    for line in section {
+        // Top-level entries; their dependencies are indented further.
+        let Some(entry) = line.strip_prefix("  - ") else {
+            continue;
+        };
+        let entry = entry.trim_end().trim_end_matches(':').trim_matches('"');
+        let Some((name, version)) = entry.split_once(" (") else {
+            continue;
+        };
+
+        let pod = name.split('/').next().unwrap_or(name).to_string();
+        let version = version.trim_end_matches(')').to_string();
+        if !pods.contains(&(pod.clone(), version.clone())) {
+            pods.push((pod, version));
+        }
+    }
+
+    pods
+}
+
+/// Pod versions locked by `projects`, each with the projects locking it.
+pub fn pods_in_use(projects: &[PathBuf]) -> HashMap<(String, String), Vec<String>> {
+    let mut in_use: HashMap<(String, String), Vec<String>> = HashMap::new();
+
+    for project in projects {
+        let locked: Vec<(String, String)> = PODFILE_LOCKS
+            .iter()
+            .filter_map(|file| std::fs::read_to_string(project.join(file)).ok())
+            .flat_map(|lockfile| parse_podfile_lock(&lockfile))
+            .collect();
+
+        let project = project.to_string_lossy().to_string();
+        for pod in locked {
+            let users = in_use.entry(pod).or_default();
+            if !users.contains(&project) {
+                users.push(project.clone());
+            }
+        }
+    }
+
+    in_use
+}
+
+/// The cache roots that exist, first one per overlapping set.
+fn pod_cache_roots() -> Vec<CandidatePath> {
+    let mut roots: Vec<CandidatePath> = Vec::new();
+
+    for location in POD_CACHE_ROOTS.iter().filter(|location| location.applies()) {
+        let Ok(path) = location.pattern_path() else {
+            continue;
+        };
+        if path.is_dir() && !roots.iter().any(|root| overlaps(&root.path, &path)) {
+            roots.push(CandidatePath {
+                path,
+                source: location.source(),
+            });
+        }
+    }
+
+    roots
+}
+
+/// The projects that lock `entry`: the same version, or for external
+/// downloads any version of the pod.
+fn locked_by(
+    entry: &PodCacheEntry,
+    in_use: &HashMap<(String, String), Vec<String>>,
+) -> Vec<String> {
+    let mut projects: Vec<String> = match &entry.version {
+        Some(version) => in_use
+            .get(&(entry.pod.clone(), version.clone()))
+            .cloned()
+            .unwrap_or_default(),
+        None => in_use
+            .iter()
+            .filter(|((pod, _), _)| *pod == entry.pod)
+            .flat_map(|(_, projects)| projects.iter().cloned())
+            .collect(),
+    };
+    projects.sort();
+    projects.dedup();
+    projects
+}
+
+impl CacheTarget for CocoaPodsCache {
+    fn base(&self) -> &CacheDefinition {
+        &self.base
+    }
+
+    fn candidates(&self) -> Result<Vec<CandidatePath>, String> {
+        let mut candidates = self.base.candidates()?;
+
+        for root in pod_cache_roots() {
+            candidates.extend(pod_cache_entries(&root.path).into_iter().map(|entry| {
+                CandidatePath {
+                    path: entry.path,
+                    source: root.source.clone(),
+                }
+            }));
+        }
+
+        Ok(candidates)
+    }
+
+    fn watch_roots(&self) -> Vec<CandidatePath> {
+        let mut roots = self.base.watch_roots();
+        roots.extend(pod_cache_roots());
+        roots
+    }
+
+    /// Pod versions a project's `Podfile.lock` still pins are reported but
+    /// not deletable.
+    fn scan(&self, context: &ScanContext) -> Result<Vec<ScanResult>, String> {
+        let mut results = self.base.scan(context)?;
+        let roots = pod_cache_roots();
+        let in_use = if roots.is_empty() {
+            HashMap::new()
+        } else {
+            pods_in_use(context.projects())
+        };
+
+        for root in roots {
+            for entry in pod_cache_entries(&root.path) {
+                if context.observer.is_cancelled() {
+                    return Ok(results);
+                }
+
+                let used_by = locked_by(&entry, &in_use);
+                let size = get_candidate_size(&entry.path, context.observer);
+                results.push(ScanResult {
+                    can_delete: used_by.is_empty(),
+                    path_source: root.source.clone(),
+                    component: entry.component.to_string(),
+                    version: entry.version,
+                    used_by,
+                    ..ScanResult::from_dir_size(&entry.path, self.id(), self.owner(), size)
+                });
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn describe(&self) -> CacheCategory {
+        let mut category = self.base.describe();
+        category.locations.extend(
+            pod_cache_roots()
+                .into_iter()
+                .map(|root| root.path.to_string_lossy().to_string()),
+        );
+        category
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PODFILE_LOCK: &str = include_str!("../../tests/fixtures/cocoapods/Podfile.lock");
+
+    fn pod(name: &str, version: &str) -> (String, String) {
+        (name.to_string(), version.to_string())
+    }
+
+    #[test]
+    fn parses_top_level_pods_of_a_lockfile() {
+        assert_eq!(
+            parse_podfile_lock(PODFILE_LOCK),
+            [
+                pod("boost", "1.83.0"),
+                pod("DoubleConversion", "1.1.6"),
+                pod("FBLazyVector", "0.73.6"),
+                pod("RCT-Folly", "2022.05.16.00"),
+                pod("React-Core", "0.73.6"),
+                pod("SDWebImage", "5.19.1"),
+                pod("Shimmer", "2.0.1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_dependency_lines_and_other_sections() {
+        let pods = parse_podfile_lock(PODFILE_LOCK);
+        assert!(!pods.iter().any(|(name, _)| name == "glog"));
+        assert!(!pods.iter().any(|(_, version)| version.starts_with('=')));
+        assert!(parse_podfile_lock("DEPENDENCIES:\n  - boost (1.83.0)\n").is_empty());
+    }
+
+    #[test]
+    fn strips_the_checksum_from_release_folders() {
+        assert_eq!(release_version("1.83.0-9a1b2"), "1.83.0");
+        assert_eq!(release_version("5.0.0-beta-abc12"), "5.0.0-beta");
+        assert_eq!(release_version("5.0.0-beta"), "5.0.0-beta");
+        assert_eq!(release_version("2022.05.16.00"), "2022.05.16.00");
+    }
+
+    fn entry(pod: &str, version: Option<&str>) -> PodCacheEntry {
+        PodCacheEntry {
+            path: PathBuf::from(pod),
+            component: if version.is_some() {
+                "pod_release"
+            } else {
+                "pod_external"
+            },
+            pod: pod.to_string(),
+            version: version.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn matches_releases_by_version_and_external_pods_by_name() {
+        let in_use = HashMap::from([
+            (pod("Shimmer", "2.0.1"), vec!["/b".to_string()]),
+            (
+                pod("Shimmer", "1.0.0"),
+                vec!["/a".to_string(), "/b".to_string()],
+            ),
+            (pod("boost", "1.83.0"), vec!["/a".to_string()]),
+        ]);
+
+        assert_eq!(locked_by(&entry("boost", Some("1.83.0")), &in_use), ["/a"]);
+        assert!(locked_by(&entry("boost", Some("1.84.0")), &in_use).is_empty());
+        assert_eq!(locked_by(&entry("Shimmer", None), &in_use), ["/a", "/b"]);
+        assert!(locked_by(&entry("Lottie", None), &in_use).is_empty());
+    }
+}
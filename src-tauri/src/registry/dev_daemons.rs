@@ -0,0 +1,393 @@
+use crate::models::{RunningDaemon, ScanResult};
+use crate::registry::{avd_dir, avd_home, emulator_avd, find_target};
+use crate::utils::run_tool;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long a daemon gets to exit after being asked to stop.
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A long-running development process that keeps cache files open or
+/// recreates them, so they should not be deleted under it.
+pub struct DaemonDefinition {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub categories: &'static [&'static str],
+    /// Recognizes the daemon from its command line.
+    pub matches: fn(&[String]) -> bool,
+    /// The folders a running instance keeps open.
+    pub holds: fn(&RunningDaemon) -> Vec<PathBuf>,
+    /// Asks the daemon to exit cleanly; SIGTERM is sent if it is still
+    /// running afterwards.
+    pub stop_command: Option<(&'static str, &'static [&'static str])>,
+}
+
+pub static DEV_DAEMONS: &[DaemonDefinition] = &[
+    DaemonDefinition {
+        id: "gradle_daemon",
+        name: "Gradle daemon",
+        categories: &["android_cache"],
+        matches: is_gradle_daemon,
+        holds: category_paths,
+        stop_command: Some(("gradle", &["--stop"])),
+    },
+    DaemonDefinition {
+        id: "kotlin_daemon",
+        name: "Kotlin compile daemon",
+        categories: &["android_cache"],
+        matches: is_kotlin_daemon,
+        holds: category_paths,
+        stop_command: None,
+    },
+    DaemonDefinition {
+        id: "watchman",
+        name: "Watchman",
+        categories: &["watchman_cache"],
+        matches: is_watchman,
+        holds: category_paths,
+        stop_command: Some(("watchman", &["shutdown-server"])),
+    },
+    DaemonDefinition {
+        id: "metro",
+        name: "Metro bundler",
+        categories: &["metro_cache", "temp_files"],
+        matches: is_metro,
+        holds: category_paths,
+        stop_command: None,
+    },
+    DaemonDefinition {
+        id: "android_emulator",
+        name: "Android Emulator",
+        categories: &[],
+        matches: is_emulator,
+        holds: running_avd_dir,
+        stop_command: None,
+    },
+];
+
+fn program_name(cmdline: &[String]) -> &str {
+    cmdline
+        .first()
+        .and_then(|program| Path::new(program).file_name())
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+}
+
+fn is_gradle_daemon(cmdline: &[String]) -> bool {
+    cmdline
+        .iter()
+        .any(|arg| arg == "org.gradle.launcher.daemon.bootstrap.GradleDaemon")
+}
+
+fn is_kotlin_daemon(cmdline: &[String]) -> bool {
+    cmdline
+        .iter()
+        .any(|arg| arg.ends_with("KotlinCompileDaemon"))
+}
+
+fn is_watchman(cmdline: &[String]) -> bool {
+    program_name(cmdline) == "watchman"
+}
+
+/// The scripts node runs for `react-native start`, `expo start` and
+/// `metro serve`, directly or through their `node_modules/.bin` links.
+const METRO_ENTRY_POINTS: &[&str] = &[
+    "react-native/cli.js",
+    "@expo/cli",
+    "expo/bin/cli",
+    "metro/src/cli.js",
+    ".bin/react-native",
+    ".bin/expo",
+    ".bin/metro",
+];
+
+fn is_metro(cmdline: &[String]) -> bool {
+    if !program_name(cmdline).starts_with("node") {
+        return false;
+    }
+
+    // The script is the first argument that is not a node option.
+    let Some(script) = cmdline.iter().skip(1).find(|arg| !arg.starts_with('-')) else {
+        return false;
+    };
+    let script = format!("/{}", script.replace('\\', "/"));
+    let runs_bundler = METRO_ENTRY_POINTS.iter().any(|entry| {
+        script.ends_with(&format!("/{entry}")) || script.contains(&format!("/{entry}/"))
+    });
+
+    runs_bundler && cmdline.iter().any(|arg| arg == "start" || arg == "serve")
+}
+
+/// The `qemu-system-*` process the `emulator` launcher starts for an AVD,
+/// which is what holds the AVD's files.
+fn is_emulator(cmdline: &[String]) -> bool {
+    program_name(cmdline).starts_with("qemu-system")
+        && cmdline
+            .iter()
+            .any(|arg| arg == "-avd" || arg.starts_with('@'))
+}
+
+/// Splits a `/proc/<pid>/cmdline` into its arguments.
+pub fn parse_cmdline(raw: &[u8]) -> Vec<String> {
+    raw.split(|byte| *byte == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).to_string())
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn running_processes() -> Vec<(u32, Vec<String>)> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| {
+            let raw = std::fs::read(format!("/proc/{pid}/cmdline")).ok()?;
+            Some((pid, parse_cmdline(&raw)))
+        })
+        .collect()
+}
+
+/// Without `/proc`, `ps` gives the command line as one string; splitting it
+/// on whitespace is enough for the patterns above.
+#[cfg(not(target_os = "linux"))]
+fn running_processes() -> Vec<(u32, Vec<String>)> {
+    let Some(output) = run_tool("ps", &["-axww", "-o", "pid=,command="]) else {
+        return Vec::new();
+    };
+
+    output
+        .lines()
+        .filter_map(|line| {
+            let (pid, command) = line.trim().split_once(' ')?;
+            let args = command.split_whitespace().map(str::to_string).collect();
+            Some((pid.parse().ok()?, args))
+        })
+        .collect()
+}
+
+pub fn running_daemons() -> Vec<RunningDaemon> {
+    let own_pid = std::process::id();
+
+    running_processes()
+        .into_iter()
+        .filter(|(pid, _)| *pid != own_pid)
+        .filter_map(|(pid, cmdline)| {
+            let daemon = DEV_DAEMONS
+                .iter()
+                .find(|daemon| (daemon.matches)(&cmdline))?;
+
+            Some(RunningDaemon {
+                id: daemon.id.to_string(),
+                name: daemon.name.to_string(),
+                pid,
+                command: cmdline.join(" "),
+                categories: daemon.categories.iter().map(|c| c.to_string()).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Lists the daemons in `daemons` that own each result's category.
+pub fn mark_running_owners(results: &mut [ScanResult], daemons: &[RunningDaemon]) {
+    for result in results {
+        result.running_owners = daemons
+            .iter()
+            .filter(|daemon| daemon.categories.contains(&result.file_type))
+            .map(|daemon| daemon.name.clone())
+            .collect();
+        result.running_owners.sort();
+        result.running_owners.dedup();
+    }
+}
+
+/// The candidates of the daemon's categories.
+fn category_paths(daemon: &RunningDaemon) -> Vec<PathBuf> {
+    daemon
+        .categories
+        .iter()
+        .filter_map(|id| find_target(id)?.candidates().ok())
+        .flatten()
+        .map(|candidate| candidate.path)
+        .collect()
+}
+
+/// The folder of the AVD the emulator runs, whose snapshots and user data
+/// it writes to.
+fn running_avd_dir(daemon: &RunningDaemon) -> Vec<PathBuf> {
+    let Some((id, home)) = emulator_avd(&daemon.command).zip(avd_home()) else {
+        return Vec::new();
+    };
+    avd_dir(&home.join(format!("{id}.ini")), &home)
+        .into_iter()
+        .collect()
+}
+
+/// The running daemons holding any of `paths` or a folder inside them.
+pub fn daemons_holding(paths: &[String], daemons: Vec<RunningDaemon>) -> Vec<RunningDaemon> {
+    daemons
+        .into_iter()
+        .filter(|daemon| {
+            let Some(definition) = DEV_DAEMONS.iter().find(|d| d.id == daemon.id) else {
+                return false;
+            };
+            (definition.holds)(daemon).iter().any(|held| {
+                paths.iter().any(|path| {
+                    let path = Path::new(path);
+                    path.starts_with(held) || held.starts_with(path)
+                })
+            })
+        })
+        .collect()
+}
+
+/// Stops `daemon`, first through its own stop command when it has one.
+pub fn stop_daemon(daemon: &RunningDaemon) -> Result<(), String> {
+    // `gradle --stop` may already have taken this one down with another.
+    if !is_running(daemon.pid) {
+        return Ok(());
+    }
+
+    let stop_command = DEV_DAEMONS
+        .iter()
+        .find(|definition| definition.id == daemon.id)
+        .and_then(|definition| definition.stop_command);
+
+    if let Some((program, args)) = stop_command {
+        if run_tool(program, args).is_some() && wait_for_exit(daemon.pid) {
+            return Ok(());
+        }
+    }
+
+    terminate(daemon.pid)?;
+    if wait_for_exit(daemon.pid) {
+        Ok(())
+    } else {
+        Err(format!("{} (pid {}) did not stop", daemon.name, daemon.pid))
+    }
+}
+
+fn wait_for_exit(pid: u32) -> bool {
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while is_running(pid) {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    true
+}
+
+/// Zombies count as stopped: they have exited and only wait to be reaped.
+#[cfg(target_os = "linux")]
+fn is_running(pid: u32) -> bool {
+    let Ok(stat) = std::fs::read_to_string(format!("/proc/{pid}/stat")) else {
+        return false;
+    };
+
+    // The state follows the parenthesized command name, which may itself
+    // contain spaces or parentheses.
+    stat.rsplit_once(") ")
+        .and_then(|(_, rest)| rest.chars().next())
+        .is_some_and(|state| state != 'Z')
+}
+
+/// `kill -0` delivers no signal, only checks that the process exists.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn is_running(pid: u32) -> bool {
+    run_tool("kill", &["-0", &pid.to_string()]).is_some()
+}
+
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    let filter = format!("PID eq {pid}");
+    run_tool("tasklist", &["/FI", &filter, "/NH", "/FO", "CSV"])
+        .is_some_and(|output| output.contains(&format!("\"{pid}\"")))
+}
+
+#[cfg(unix)]
+fn terminate(pid: u32) -> Result<(), String> {
+    run_tool("kill", &["-TERM", &pid.to_string()])
+        .map(|_| ())
+        .ok_or_else(|| format!("Failed to send SIGTERM to pid {pid}"))
+}
+
+#[cfg(windows)]
+fn terminate(pid: u32) -> Result<(), String> {
+    run_tool("taskkill", &["/PID", &pid.to_string()])
+        .map(|_| ())
+        .ok_or_else(|| format!("Failed to stop pid {pid}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(command: &str) -> Vec<String> {
+        command.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn splits_proc_cmdlines_on_nul_bytes() {
+        assert_eq!(
+            parse_cmdline(b"node\0/app/node_modules/.bin/react-native\0start\0\0"),
+            ["node", "/app/node_modules/.bin/react-native", "start"]
+        );
+        assert!(parse_cmdline(b"").is_empty());
+    }
+
+    #[test]
+    fn recognizes_metro_under_node() {
+        assert!(is_metro(&args(
+            "node /app/node_modules/.bin/react-native start"
+        )));
+        assert!(is_metro(&args(
+            "node /app/node_modules/react-native/cli.js start --reset-cache"
+        )));
+        assert!(is_metro(&args(
+            "/usr/local/bin/node /app/node_modules/.bin/expo start --clear"
+        )));
+        assert!(is_metro(&args(
+            "node --max-old-space-size=8192 /app/node_modules/@expo/cli/build/bin/cli start"
+        )));
+        assert!(is_metro(&args("node /app/node_modules/expo/bin/cli start")));
+        assert!(is_metro(&args(
+            "node20 /app/node_modules/metro/src/cli.js serve"
+        )));
+        assert!(!is_metro(&args(
+            "node /app/node_modules/.bin/react-native run-android"
+        )));
+        assert!(!is_metro(&args("node /app/server.js start")));
+        assert!(!is_metro(&args(
+            "node /work/react-native-app/server.js start"
+        )));
+        assert!(!is_metro(&args("node /work/expo-demo/index.js start")));
+        assert!(!is_metro(&args("ruby react-native start")));
+    }
+
+    #[test]
+    fn recognizes_gradle_daemons_by_main_class() {
+        assert!(is_gradle_daemon(&args(
+            "/usr/lib/jvm/java-17/bin/java -Xmx2048m -cp gradle-launcher-8.3.jar \
+             org.gradle.launcher.daemon.bootstrap.GradleDaemon 8.3"
+        )));
+        assert!(!is_gradle_daemon(&args(
+            "java -cp gradle-wrapper.jar org.gradle.wrapper.GradleWrapperMain assembleDebug"
+        )));
+        assert!(!is_kotlin_daemon(&args(
+            "java org.gradle.launcher.daemon.bootstrap.GradleDaemon 8.3"
+        )));
+    }
+
+    #[test]
+    fn recognizes_the_emulator_process_running_an_avd() {
+        assert!(is_emulator(&args(
+            "/sdk/emulator/qemu/linux-x86_64/qemu-system-x86_64 -netdelay none -avd Pixel_7"
+        )));
+        assert!(!is_emulator(&args("/sdk/emulator/emulator -avd Pixel_7")));
+        assert!(!is_emulator(&args("qemu-system-x86_64 -hda disk.img")));
+    }
+}
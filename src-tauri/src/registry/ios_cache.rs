@@ -0,0 +1,473 @@
+use crate::models::{CacheCategory, ScanResult};
+use crate::projects::workspace_config;
+use crate::registry::{
+    CacheDefinition, CacheTarget, CandidatePath, Location, Platform, ScanContext,
+};
+use crate::utils::{get_candidate_size, subdirs, unix_secs, SizeObserver};
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+const DERIVED_DATA: Location =
+    Location::home(Platform::MacOs, "Library/Developer/Xcode/DerivedData");
+
+/// Symbols copied from each connected device's OS, with the component their
+/// entries are reported as.
+const DEVICE_SUPPORT: &[(Location, &str)] = &[
+    (
+        Location::home(Platform::MacOs, "Library/Developer/Xcode/iOS DeviceSupport"),
+        "ios_device_support",
+    ),
+    (
+        Location::home(
+            Platform::MacOs,
+            "Library/Developer/Xcode/watchOS DeviceSupport",
+        ),
+        "watchos_device_support",
+    ),
+    (
+        Location::home(
+            Platform::MacOs,
+            "Library/Developer/Xcode/tvOS DeviceSupport",
+        ),
+        "tvos_device_support",
+    ),
+];
+
+/// Xcode appends a 28-letter hash of the workspace path to the project name.
+const WORKSPACE_HASH_LEN: usize = 28;
+
+/// The iOS category, with DerivedData split into one entry per workspace and
+/// DeviceSupport into one entry per OS version, instead of folders holding
+/// every project's build products and every device's symbols.
+pub struct IosCache {
+    pub base: CacheDefinition,
+}
+
+/// What Xcode records about a DerivedData folder in its `info.plist`.
+#[derive(Debug, Clone, Default)]
+pub struct DerivedDataInfo {
+    pub workspace_path: Option<String>,
+    /// Unix seconds.
+    pub last_accessed_at: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InfoPlist {
+    workspace_path: Option<String>,
+    last_accessed_date: Option<plist::Date>,
+}
+
+/// A folder in DerivedData. Shared folders such as `ModuleCache.noindex`
+/// have no project.
+#[derive(Debug, Clone)]
+pub struct DerivedDataEntry {
+    pub path: PathBuf,
+    pub project: Option<String>,
+    pub info: DerivedDataInfo,
+}
+
+/// The OS a DeviceSupport folder holds symbols for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceSupportVersion {
+    pub os_version: String,
+    pub build: String,
+}
+
+/// Parses a DerivedData `info.plist`, in either the XML or binary format.
+pub fn parse_derived_data_info(contents: &[u8]) -> Option<DerivedDataInfo> {
+    let info: InfoPlist = plist::from_bytes(contents).ok()?;
+
+    Some(DerivedDataInfo {
+        workspace_path: info.workspace_path,
+        last_accessed_at: info
+            .last_accessed_date
+            .and_then(|date| unix_secs(Ok(date.into()))),
+    })
+}
+
+/// The project name in a `<Project>-<hash>` folder name.
+fn project_name(folder: &str) -> Option<&str> {
+    let (project, hash) = folder.rsplit_once('-')?;
+    let is_hash = hash.len() == WORKSPACE_HASH_LEN && hash.chars().all(|c| c.is_ascii_lowercase());
+
+    (is_hash && !project.is_empty()).then_some(project)
+}
+
+/// The folders in the DerivedData directory `dir`, with their `info.plist`
+/// read when they have one.
+pub fn derived_data_entries(dir: &Path) -> Vec<DerivedDataEntry> {
+    subdirs(dir)
+        .into_iter()
+        .map(|(name, path)| {
+            let info = std::fs::read(path.join("info.plist"))
+                .ok()
+                .and_then(|contents| parse_derived_data_info(&contents))
+                .unwrap_or_default();
+
+            DerivedDataEntry {
+                project: project_name(&name).map(str::to_string),
+                path,
+                info,
+            }
+        })
+        .collect()
+}
+
+/// Parses a DeviceSupport folder name: `17.4 (21E219) arm64e`, `16.4.1
+/// (20E252)`, or with a device model first, `iPhone15,2 17.4 (21E219)`.
+pub fn parse_device_support_name(name: &str) -> Option<DeviceSupportVersion> {
+    let (os, rest) = name.split_once(" (")?;
+    let (build, _arch) = rest.split_once(')')?;
+    let os_version = os.rsplit(' ').next()?;
+
+    (parse_version(os_version).is_some() && !build.is_empty()).then(|| DeviceSupportVersion {
+        os_version: os_version.to_string(),
+        build: build.to_string(),
+    })
+}
+
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// The major.minor of a dotted version, by which DeviceSupport is retained.
+fn release(version: &str) -> Option<(u32, u32)> {
+    let parts = parse_version(version)?;
+    Some((*parts.first()?, parts.get(1).copied().unwrap_or(0)))
+}
+
+/// Marks entries whose `version` is among the `keep` latest major.minor
+/// releases of their component as retained and not deletable.
+pub fn retain_latest_releases(results: &mut [ScanResult], keep: usize) {
+    let mut releases: Vec<(&str, (u32, u32))> = results
+        .iter()
+        .filter_map(|result| {
+            let release = release(result.version.as_deref()?)?;
+            Some((result.component.as_str(), release))
+        })
+        .collect();
+    releases.sort_by_key(|(component, release)| (*component, Reverse(*release)));
+    releases.dedup();
+
+    let kept: Vec<(String, (u32, u32))> = releases
+        .chunk_by(|a, b| a.0 == b.0)
+        .flat_map(|group| group.iter().take(keep))
+        .map(|(component, release)| (component.to_string(), *release))
+        .collect();
+
+    for result in results {
+        let Some(release) = result.version.as_deref().and_then(release) else {
+            continue;
+        };
+        if kept.contains(&(result.component.clone(), release)) {
+            result.retained = true;
+            result.can_delete = false;
+        }
+    }
+}
+
+/// Marks the `keep` most recently used per-workspace entries as retained and
+/// not deletable. Entries whose workspace is gone are never kept.
+pub fn retain_recent(results: &mut [ScanResult], keep: usize) {
+    let mut kept: Vec<&mut ScanResult> = results
+        .iter_mut()
+        .filter(|result| result.origin.is_some() && !result.origin_missing)
+        .collect();
+    kept.sort_by_key(|result| Reverse(result.last_used_at));
+
+    for result in kept.into_iter().take(keep) {
+        result.retained = true;
+        result.can_delete = false;
+    }
+}
+
+fn derived_data_dir() -> Option<CandidatePath> {
+    if !DERIVED_DATA.applies() {
+        return None;
+    }
+
+    Some(CandidatePath {
+        path: DERIVED_DATA.pattern_path().ok()?,
+        source: DERIVED_DATA.source(),
+    })
+}
+
+fn device_support_dirs() -> Vec<(CandidatePath, &'static str)> {
+    DEVICE_SUPPORT
+        .iter()
+        .filter(|(location, _)| location.applies())
+        .filter_map(|(location, component)| {
+            let dir = CandidatePath {
+                path: location.pattern_path().ok()?,
+                source: location.source(),
+            };
+            Some((dir, *component))
+        })
+        .collect()
+}
+
+impl IosCache {
+    fn scan_derived_data(&self, observer: &dyn SizeObserver) -> Vec<ScanResult> {
+        let Some(dir) = derived_data_dir() else {
+            return Vec::new();
+        };
+
+        let mut results = Vec::new();
+        for entry in derived_data_entries(&dir.path) {
+            if observer.is_cancelled() {
+                break;
+            }
+
+            let size = get_candidate_size(&entry.path, observer);
+            let last_used_at = entry.info.last_accessed_at.or(size.newest_file_at);
+            let component = if entry.project.is_some() {
+                "derived_data"
+            } else {
+                "derived_data_shared"
+            };
+            let origin = entry.info.workspace_path;
+
+            results.push(ScanResult {
+                path_source: dir.source.clone(),
+                component: component.to_string(),
+                origin_missing: origin
+                    .as_ref()
+                    .is_some_and(|workspace| !Path::new(workspace).exists()),
+                origin,
+                last_used_at,
+                ..ScanResult::from_dir_size(&entry.path, self.id(), "Xcode", size)
+            });
+        }
+
+        retain_recent(&mut results, workspace_config().derived_data_keep);
+        results
+    }
+
+    fn scan_device_support(&self, observer: &dyn SizeObserver) -> Vec<ScanResult> {
+        let mut results = Vec::new();
+        for (dir, component) in device_support_dirs() {
+            for (name, path) in subdirs(&dir.path) {
+                if observer.is_cancelled() {
+                    return results;
+                }
+
+                let parsed = parse_device_support_name(&name);
+                let size = get_candidate_size(&path, observer);
+                results.push(ScanResult {
+                    path_source: dir.source.clone(),
+                    component: component.to_string(),
+                    version: parsed.as_ref().map(|parsed| parsed.os_version.clone()),
+                    build: parsed.map(|parsed| parsed.build),
+                    ..ScanResult::from_dir_size(&path, self.id(), "Xcode", size)
+                });
+            }
+        }
+
+        retain_latest_releases(&mut results, workspace_config().device_support_keep);
+        results
+    }
+}
+
+impl CacheTarget for IosCache {
+    fn base(&self) -> &CacheDefinition {
+        &self.base
+    }
+
+    fn candidates(&self) -> Result<Vec<CandidatePath>, String> {
+        let mut candidates = self.base.candidates()?;
+
+        if let Some(dir) = derived_data_dir() {
+            candidates.extend(derived_data_entries(&dir.path).into_iter().map(|entry| {
+                CandidatePath {
+                    path: entry.path,
+                    source: dir.source.clone(),
+                }
+            }));
+        }
+        for (dir, _) in device_support_dirs() {
+            candidates.extend(
+                subdirs(&dir.path)
+                    .into_iter()
+                    .map(|(_, path)| CandidatePath {
+                        path,
+                        source: dir.source.clone(),
+                    }),
+            );
+        }
+
+        Ok(candidates)
+    }
+
+    fn watch_roots(&self) -> Vec<CandidatePath> {
+        let mut roots = self.base.watch_roots();
+        roots.extend(derived_data_dir());
+        roots.extend(device_support_dirs().into_iter().map(|(dir, _)| dir));
+        roots
+    }
+
+    /// The most recently built workspaces' DerivedData and the latest OS
+    /// releases' DeviceSupport are kept, per the workspace config.
+    fn scan(&self, context: &ScanContext) -> Result<Vec<ScanResult>, String> {
+        let mut results = self.base.scan(context)?;
+        results.extend(self.scan_derived_data(context.observer));
+        results.extend(self.scan_device_support(context.observer));
+        Ok(results)
+    }
+
+    fn describe(&self) -> CacheCategory {
+        let mut category = self.base.describe();
+        let dirs = derived_data_dir()
+            .into_iter()
+            .chain(device_support_dirs().into_iter().map(|(dir, _)| dir));
+        category
+            .locations
+            .extend(dirs.map(|dir| dir.path.to_string_lossy().to_string()));
+        category
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML_INFO: &[u8] = include_bytes!("../../tests/fixtures/derived_data/info.plist");
+    const BINARY_INFO: &[u8] =
+        include_bytes!("../../tests/fixtures/derived_data/info-binary.plist");
+
+    #[test]
+    fn parses_xml_info_plist() {
+        let info = parse_derived_data_info(XML_INFO).unwrap();
+        assert_eq!(
+            info.workspace_path.as_deref(),
+            Some("/Users/dev/Projects/Shop/ios/Shop.xcworkspace")
+        );
+        assert_eq!(info.last_accessed_at, Some(1_714_557_600));
+    }
+
+    #[test]
+    fn parses_binary_info_plist() {
+        let info = parse_derived_data_info(BINARY_INFO).unwrap();
+        assert_eq!(
+            info.workspace_path.as_deref(),
+            Some("/Users/dev/Projects/Legacy/ios/Legacy.xcodeproj")
+        );
+        assert_eq!(info.last_accessed_at, Some(1_709_368_200));
+    }
+
+    #[test]
+    fn rejects_malformed_info_plist() {
+        assert!(parse_derived_data_info(b"not a plist").is_none());
+    }
+
+    #[test]
+    fn project_name_needs_a_28_letter_hash() {
+        assert_eq!(
+            project_name("Shop-bxkqtzfhwcnyjqabcdefghijklmn"),
+            Some("Shop")
+        );
+        assert_eq!(
+            project_name("My-App-bxkqtzfhwcnyjqabcdefghijklmn"),
+            Some("My-App")
+        );
+        assert_eq!(project_name("Shop-BXKQTZFHWCNYJQABCDEFGHIJKLMN"), None);
+        assert_eq!(project_name("-bxkqtzfhwcnyjqabcdefghijklmn"), None);
+        assert_eq!(project_name("Shop-bxkqtzfhwcnyjqabcdefghijkl"), None);
+        assert_eq!(project_name("Shop-bxkqtzfhwcnyjqabcdefghijklmnop"), None);
+        assert_eq!(project_name("ModuleCache.noindex"), None);
+    }
+
+    fn entry(origin: Option<&str>, origin_missing: bool, last_used_at: u64) -> ScanResult {
+        ScanResult {
+            can_delete: true,
+            origin: origin.map(str::to_string),
+            origin_missing,
+            last_used_at: Some(last_used_at),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn retains_most_recently_built_workspaces() {
+        let mut results = vec![
+            entry(Some("/old.xcworkspace"), false, 100),
+            entry(Some("/newest.xcworkspace"), false, 400),
+            entry(Some("/gone.xcworkspace"), true, 500),
+            entry(None, false, 600),
+            entry(Some("/recent.xcworkspace"), false, 300),
+        ];
+
+        retain_recent(&mut results, 2);
+
+        let retained: Vec<bool> = results.iter().map(|result| result.retained).collect();
+        assert_eq!(retained, [false, true, false, false, true]);
+        for result in &results {
+            assert_eq!(result.can_delete, !result.retained);
+        }
+    }
+
+    #[test]
+    fn keeping_none_leaves_everything_deletable() {
+        let mut results = vec![entry(Some("/a.xcworkspace"), false, 100)];
+        retain_recent(&mut results, 0);
+        assert!(!results[0].retained);
+        assert!(results[0].can_delete);
+    }
+
+    #[test]
+    fn parses_device_support_folder_names() {
+        let version = |os_version: &str, build: &str| {
+            Some(DeviceSupportVersion {
+                os_version: os_version.to_string(),
+                build: build.to_string(),
+            })
+        };
+
+        assert_eq!(
+            parse_device_support_name("17.4 (21E219)"),
+            version("17.4", "21E219")
+        );
+        assert_eq!(
+            parse_device_support_name("iPhone15,2 17.4 (21E219)"),
+            version("17.4", "21E219")
+        );
+        assert_eq!(
+            parse_device_support_name("16.4.1 (20E252) arm64e"),
+            version("16.4.1", "20E252")
+        );
+        assert_eq!(parse_device_support_name("17.4"), None);
+        assert_eq!(parse_device_support_name("17.4 ()"), None);
+        assert_eq!(parse_device_support_name("Latest (21E219)"), None);
+    }
+
+    fn device_support(platform: &str, version: &str) -> ScanResult {
+        ScanResult {
+            can_delete: true,
+            component: platform.to_string(),
+            version: Some(version.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keeps_the_latest_releases_of_each_platform() {
+        let mut results = vec![
+            device_support("iOS", "16.4"),
+            device_support("iOS", "17.4"),
+            device_support("iOS", "17.4.1"),
+            device_support("iOS", "17.2"),
+            device_support("watchOS", "9.1"),
+            device_support("iOS", "15.0"),
+            device_support("watchOS", "10.4"),
+        ];
+
+        retain_latest_releases(&mut results, 2);
+
+        let retained: Vec<bool> = results.iter().map(|result| result.retained).collect();
+        assert_eq!(retained, [false, true, true, true, true, false, true]);
+        for result in &results {
+            assert_eq!(result.can_delete, !result.retained);
+        }
+    }
+}
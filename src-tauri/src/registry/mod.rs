@@ -0,0 +1,19 @@
+pub mod android_avd;
+pub mod android_cache;
+pub mod android_sdk;
+pub mod cache_target;
+pub mod categories;
+pub mod cocoapods_cache;
+pub mod dev_daemons;
+pub mod ios_cache;
+pub mod scan_context;
+
+pub use android_avd::*;
+pub use android_cache::*;
+pub use android_sdk::*;
+pub use cache_target::*;
+pub use categories::*;
+pub use cocoapods_cache::*;
+pub use dev_daemons::*;
+pub use ios_cache::*;
+pub use scan_context::*;
@@ -0,0 +1,32 @@
+use crate::projects::{discover_projects, workspace_config, workspace_roots};
+use crate::utils::{SizeObserver, ToolQueryScope};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// What the targets scanned in one pass share: the observer, and the
+/// workspace projects several breakdowns check their entries against. Tools
+/// are asked for their cache locations while a context is alive.
+pub struct ScanContext<'a> {
+    pub observer: &'a dyn SizeObserver,
+    projects: OnceLock<Vec<PathBuf>>,
+    _tool_queries: ToolQueryScope<'static>,
+}
+
+impl<'a> ScanContext<'a> {
+    pub fn new(observer: &'a dyn SizeObserver) -> Self {
+        ScanContext {
+            observer,
+            projects: OnceLock::new(),
+            _tool_queries: ToolQueryScope::enter(),
+        }
+    }
+
+    /// The projects under the workspace roots, discovered by the first
+    /// target that asks and reused by the rest of the pass.
+    pub fn projects(&self) -> &[PathBuf] {
+        self.projects.get_or_init(|| {
+            let roots = workspace_roots(&workspace_config());
+            discover_projects(&roots, self.observer)
+        })
+    }
+}
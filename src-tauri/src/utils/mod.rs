@@ -1,5 +1,15 @@
 pub mod docker_helpers;
+pub mod path_glob;
 pub mod size_calculator;
+pub mod size_index;
+#[cfg(test)]
+pub mod test_env;
+pub mod tool_query;
 
 pub use docker_helpers::*;
+pub use path_glob::*;
 pub use size_calculator::*;
+pub use size_index::*;
+#[cfg(test)]
+pub use test_env::*;
+pub use tool_query::*;
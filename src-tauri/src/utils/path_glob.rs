@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+
+/// Returns true when `pattern` contains any glob metacharacter.
+pub fn is_glob_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Expands `pattern` relative to `base`, supporting `*`, `?`, `**` and
+/// character classes. Literal patterns resolve to the joined path whether or
+/// not it exists; glob patterns only return existing matches. An empty
+/// pattern is `base` itself.
+pub fn expand_glob(base: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+    if pattern.is_empty() {
+        return Ok(vec![base.to_path_buf()]);
+    }
+    if !is_glob_pattern(pattern) {
+        return Ok(vec![base.join(pattern)]);
+    }
+
+    let escaped_base = glob::Pattern::escape(&base.to_string_lossy());
+    let full_pattern = Path::new(&escaped_base).join(pattern);
+
+    let matches = glob::glob(&full_pattern.to_string_lossy())
+        .map_err(|e| format!("Invalid glob pattern {pattern}: {e}"))?
+        .filter_map(|entry| entry.ok())
+        .collect();
+
+    Ok(matches)
+}
+
+/// The subdirectories of `dir` with their names; empty when it is unreadable.
+pub fn subdirs(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().to_string(),
+                entry.path(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(base: &Path, pattern: &str) -> Vec<PathBuf> {
+        let mut paths = expand_glob(base, pattern).unwrap();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn expands_patterns_below_a_base_with_glob_characters() {
+        let temp = tempfile::tempdir().unwrap();
+        let base = temp.path().join("we[ird]");
+        for dir in [
+            "metro-1",
+            "metro-22",
+            "haste-map-x",
+            "a/cache",
+            "a/b/c/cache",
+        ] {
+            std::fs::create_dir_all(base.join(dir)).unwrap();
+        }
+        for file in ["fileA", "fileB"] {
+            std::fs::write(base.join(file), "").unwrap();
+        }
+
+        assert_eq!(
+            matches(&base, "metro-*"),
+            [base.join("metro-1"), base.join("metro-22")]
+        );
+        assert_eq!(matches(&base, "metro-?"), [base.join("metro-1")]);
+        assert_eq!(
+            matches(&base, "**/cache"),
+            [base.join("a/b/c/cache"), base.join("a/cache")]
+        );
+        assert_eq!(
+            matches(&base, "file[AB]"),
+            [base.join("fileA"), base.join("fileB")]
+        );
+        assert_eq!(matches(&base, "file[!A]"), [base.join("fileB")]);
+        assert_eq!(matches(&base, "missing"), [base.join("missing")]);
+        assert_eq!(matches(&base, ""), [base.as_path()]);
+        assert!(matches(&base, "nothing-*").is_empty());
+    }
+}
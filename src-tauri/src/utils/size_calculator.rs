@@ -1,12 +1,578 @@
-use walkdir::WalkDir;
+use crate::utils::{size_index, IndexBatch, SizeIndex};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub fn get_dir_size(path: &std::path::Path) -> Result<u64, std::io::Error> {
-    let mut size = 0;
-    for entry in WalkDir::new(path) {
-        let entry = entry?;
-        if entry.file_type().is_file() {
-            size += entry.metadata()?.len();
+static SCAN_POOL: OnceLock<RwLock<Arc<ThreadPool>>> = OnceLock::new();
+
+pub fn default_thread_budget() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+fn build_pool(threads: usize) -> Result<ThreadPool, String> {
+    ThreadPoolBuilder::new()
+        .num_threads(threads.max(1))
+        .thread_name(|i| format!("scan-worker-{i}"))
+        .build()
+        .map_err(|e| format!("Failed to start scan workers: {e}"))
+}
+
+fn pool_slot() -> &'static RwLock<Arc<ThreadPool>> {
+    SCAN_POOL.get_or_init(|| {
+        let pool = build_pool(default_thread_budget()).expect("failed to start scan workers");
+        RwLock::new(Arc::new(pool))
+    })
+}
+
+/// The work-stealing pool every scan pass sizes directories on.
+pub fn scan_pool() -> Arc<ThreadPool> {
+    pool_slot()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Replaces the shared pool; passes already running keep their old workers.
+pub fn set_thread_budget(threads: usize) -> Result<(), String> {
+    let pool = build_pool(threads)?;
+    *pool_slot().write().unwrap_or_else(|e| e.into_inner()) = Arc::new(pool);
+    Ok(())
+}
+
+/// How many unreadable paths or symlinks are kept verbatim for display.
+const SAMPLE_LIMIT: usize = 10;
+
+fn extend_sample(sample: &mut Vec<String>, other: Vec<String>) {
+    let room = SAMPLE_LIMIT.saturating_sub(sample.len());
+    sample.extend(other.into_iter().take(room));
+}
+
+/// Result of sizing a tree. Unreadable entries are skipped rather than
+/// failing the whole walk, so sizes are lower bounds when any were hit.
+///
+/// Hard-linked files are counted once. `bytes` is the apparent size and
+/// `allocated_bytes` what the files occupy on disk; `linked_elsewhere_*`
+/// covers files that still have links outside the tree, whose space is not
+/// freed by deleting it.
+///
+/// Symlinks are never followed. Links resolving outside the sized root are
+/// reported separately since the root's size says nothing about them.
+///
+/// Timestamps are Unix seconds. `modified_at` is the root's own mtime; the
+/// other two are the newest values found among the files below it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirSize {
+    pub bytes: u64,
+    pub allocated_bytes: u64,
+    pub linked_elsewhere_bytes: u64,
+    pub linked_elsewhere_files: u64,
+    pub unreadable_count: u64,
+    pub unreadable_sample: Vec<String>,
+    pub external_symlink_count: u64,
+    pub external_symlink_sample: Vec<String>,
+    /// The walk was cancelled before the whole tree was visited.
+    pub interrupted: bool,
+    pub file_count: u64,
+    /// Files, directories and symlinks below the root.
+    pub item_count: u64,
+    pub modified_at: Option<u64>,
+    pub accessed_at: Option<u64>,
+    pub newest_file_at: Option<u64>,
+}
+
+pub fn unix_secs(time: std::io::Result<SystemTime>) -> Option<u64> {
+    time.ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+impl DirSize {
+    fn file(metadata: &Metadata, allocated_bytes: u64) -> Self {
+        DirSize {
+            bytes: metadata.len(),
+            allocated_bytes,
+            file_count: 1,
+            item_count: 1,
+            accessed_at: unix_secs(metadata.accessed()),
+            newest_file_at: unix_secs(metadata.modified()),
+            ..Default::default()
+        }
+    }
+
+    /// A directory or symlink entry, counted but sized separately.
+    fn item() -> Self {
+        DirSize {
+            item_count: 1,
+            ..Default::default()
+        }
+    }
+
+    fn unreadable(path: &Path) -> Self {
+        DirSize {
+            unreadable_count: 1,
+            unreadable_sample: vec![path.to_string_lossy().to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn external_symlink(path: &Path) -> Self {
+        DirSize {
+            external_symlink_count: 1,
+            external_symlink_sample: vec![path.to_string_lossy().to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn interrupted() -> Self {
+        DirSize {
+            interrupted: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn merge(mut self, other: DirSize) -> Self {
+        self.bytes += other.bytes;
+        self.allocated_bytes += other.allocated_bytes;
+        self.linked_elsewhere_bytes += other.linked_elsewhere_bytes;
+        self.linked_elsewhere_files += other.linked_elsewhere_files;
+        self.unreadable_count += other.unreadable_count;
+        extend_sample(&mut self.unreadable_sample, other.unreadable_sample);
+        self.external_symlink_count += other.external_symlink_count;
+        extend_sample(
+            &mut self.external_symlink_sample,
+            other.external_symlink_sample,
+        );
+        self.interrupted |= other.interrupted;
+        self.file_count += other.file_count;
+        self.item_count += other.item_count;
+        self.modified_at = self.modified_at.max(other.modified_at);
+        self.accessed_at = self.accessed_at.max(other.accessed_at);
+        self.newest_file_at = self.newest_file_at.max(other.newest_file_at);
+        self
+    }
+
+    /// Disk space actually released by deleting the tree.
+    pub fn reclaimable_bytes(&self) -> u64 {
+        self.allocated_bytes
+            .saturating_sub(self.linked_elsewhere_bytes)
+    }
+}
+
+struct SeenLink {
+    nlink: u64,
+    seen: u64,
+    allocated_bytes: u64,
+}
+
+/// Receives progress while a tree is sized. Called from worker threads.
+pub trait SizeObserver: Sync {
+    fn entered_dir(&self, _path: &Path) {}
+    fn counted_bytes(&self, _bytes: u64) {}
+
+    /// Polled once per directory; returning true stops the walk early.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Observer for callers that only want the final size.
+pub struct NoProgress;
+
+impl SizeObserver for NoProgress {}
+
+/// State shared by every worker sizing one tree.
+struct SizeWalk<'a> {
+    root: PathBuf,
+    observer: &'a dyn SizeObserver,
+    /// Set for category candidates, the only walks the index covers.
+    index: Option<&'a SizeIndex>,
+    /// Directories read by this walk, stored in the index once it ends.
+    batch: IndexBatch,
+    /// Hard links met so far, keyed by (device, inode).
+    links: Mutex<HashMap<(u64, u64), SeenLink>>,
+}
+
+impl<'a> SizeWalk<'a> {
+    fn new(root: &Path, observer: &'a dyn SizeObserver, index: Option<&'a SizeIndex>) -> Self {
+        SizeWalk {
+            root: std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf()),
+            observer,
+            index,
+            batch: IndexBatch::default(),
+            links: Mutex::default(),
+        }
+    }
+
+    /// Sizes a regular file, returning zero for repeat links to an inode
+    /// that was already counted.
+    #[cfg(unix)]
+    fn file_size(&self, metadata: &Metadata) -> DirSize {
+        use std::os::unix::fs::MetadataExt;
+
+        let allocated_bytes = metadata.blocks() * 512;
+        if metadata.nlink() > 1 {
+            let mut links = self.links.lock().unwrap_or_else(|e| e.into_inner());
+            let link = links
+                .entry((metadata.dev(), metadata.ino()))
+                .or_insert(SeenLink {
+                    nlink: metadata.nlink(),
+                    seen: 0,
+                    allocated_bytes,
+                });
+            link.seen += 1;
+            if link.seen > 1 {
+                return DirSize {
+                    bytes: 0,
+                    ..DirSize::file(metadata, 0)
+                };
+            }
+        }
+
+        DirSize::file(metadata, allocated_bytes)
+    }
+
+    #[cfg(not(unix))]
+    fn file_size(&self, metadata: &Metadata) -> DirSize {
+        DirSize::file(metadata, metadata.len())
+    }
+
+    /// Symlinks are never followed; only links leaving the root are reported.
+    fn symlink(&self, path: &Path) -> DirSize {
+        if self.points_inside_root(path) {
+            DirSize::item()
+        } else {
+            DirSize::external_symlink(path).merge(DirSize::item())
+        }
+    }
+
+    fn points_inside_root(&self, link: &Path) -> bool {
+        let Ok(target) = std::fs::read_link(link) else {
+            return false;
+        };
+        let target = match link.parent() {
+            Some(parent) if target.is_relative() => parent.join(target),
+            _ => target,
+        };
+        let target = std::fs::canonicalize(&target).unwrap_or(target);
+
+        target.starts_with(&self.root)
+    }
+
+    /// Files whose link count was not exhausted inside the walked tree.
+    fn linked_elsewhere(&self) -> DirSize {
+        let links = self.links.lock().unwrap_or_else(|e| e.into_inner());
+        links.values().filter(|link| link.seen < link.nlink).fold(
+            DirSize::default(),
+            |mut size, link| {
+                size.linked_elsewhere_bytes += link.allocated_bytes;
+                size.linked_elsewhere_files += 1;
+                size
+            },
+        )
+    }
+}
+
+pub fn get_dir_size_observed(path: &Path, observer: &dyn SizeObserver) -> DirSize {
+    sized_walk(path, observer, None)
+}
+
+/// Sizes a category candidate through the size index and records its
+/// total, so the category list and the cache watcher can show it without a
+/// walk. Parts of a candidate (packages, AVD files, project folders) are
+/// sized with [`get_dir_size_observed`] and stay out of the index.
+pub fn get_candidate_size(path: &Path, observer: &dyn SizeObserver) -> DirSize {
+    let Some(index) = size_index() else {
+        return get_dir_size_observed(path, observer);
+    };
+
+    let size = sized_walk(path, observer, Some(index));
+    if !size.interrupted {
+        index.record_root(path, &size);
+    }
+
+    size
+}
+
+fn sized_walk(path: &Path, observer: &dyn SizeObserver, index: Option<&SizeIndex>) -> DirSize {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return DirSize::unreadable(path),
+    };
+    if metadata.file_type().is_symlink() {
+        return DirSize::external_symlink(path);
+    }
+
+    let walk = SizeWalk::new(path, observer, index);
+    let size = scan_pool().install(|| dir_size(path, &metadata, &walk));
+    let mut size = size.merge(walk.linked_elsewhere());
+    size.modified_at = unix_secs(metadata.modified());
+
+    if let Some(index) = index {
+        index.store(walk.batch);
+    }
+    size
+}
+
+/// A directory entry sorted by how it contributes to its parent's size.
+enum Child {
+    File { size: DirSize, indexable: bool },
+    Dir(String, PathBuf, Metadata),
+    Symlink(String, PathBuf),
+    Unreadable(PathBuf),
+}
+
+fn dir_size(path: &Path, metadata: &Metadata, walk: &SizeWalk) -> DirSize {
+    if metadata.is_file() {
+        let size = walk.file_size(metadata);
+        walk.observer.counted_bytes(size.bytes);
+        return size;
+    }
+    if !metadata.is_dir() {
+        return DirSize::default();
+    }
+
+    if walk.observer.is_cancelled() {
+        return DirSize::interrupted();
+    }
+    walk.observer.entered_dir(path);
+
+    if let Some(indexed) = walk.index.and_then(|index| index.lookup(path, metadata)) {
+        walk.observer.counted_bytes(indexed.files.bytes);
+        let subdirs = indexed.subdirs.par_iter().map(|name| {
+            let subdir = path.join(name);
+            match std::fs::symlink_metadata(&subdir) {
+                Ok(metadata) => dir_size(&subdir, &metadata, walk).merge(DirSize::item()),
+                Err(_) => DirSize::unreadable(&subdir),
+            }
+        });
+        let symlinks = indexed
+            .symlinks
+            .par_iter()
+            .map(|name| walk.symlink(&path.join(name)));
+
+        return subdirs
+            .chain(symlinks)
+            .reduce(DirSize::default, DirSize::merge)
+            .merge(indexed.files);
+    }
+
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return DirSize::unreadable(path),
+    };
+
+    let mut read_errors = DirSize::default();
+    let entries: Vec<_> = entries
+        .filter_map(|entry| match entry {
+            Ok(entry) => Some(entry),
+            Err(_) => {
+                read_errors = std::mem::take(&mut read_errors).merge(DirSize::unreadable(path));
+                None
+            }
+        })
+        .collect();
+
+    let children: Vec<Child> = entries
+        .par_iter()
+        .map(|entry| {
+            let entry_path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            // DirEntry::metadata does not traverse symlinks.
+            match entry.metadata() {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    Child::Symlink(name, entry_path)
+                }
+                Ok(metadata) if metadata.is_dir() => Child::Dir(name, entry_path, metadata),
+                Ok(metadata) => {
+                    let size = dir_size(&entry_path, &metadata, walk);
+                    Child::File {
+                        size,
+                        indexable: !is_hard_linked(&metadata),
+                    }
+                }
+                Err(_) => Child::Unreadable(entry_path),
+            }
+        })
+        .collect();
+
+    let mut indexable = read_errors.unreadable_count == 0;
+    let mut files = read_errors;
+    let mut subdirs = Vec::new();
+    let mut symlinks = Vec::new();
+    for child in children {
+        match child {
+            Child::File {
+                size,
+                indexable: file_indexable,
+            } => {
+                indexable &= file_indexable;
+                files = files.merge(size);
+            }
+            Child::Dir(name, dir_path, metadata) => subdirs.push((name, dir_path, metadata)),
+            Child::Symlink(name, link_path) => symlinks.push((name, link_path)),
+            Child::Unreadable(entry_path) => {
+                indexable = false;
+                files = files.merge(DirSize::unreadable(&entry_path));
+            }
         }
     }
-    Ok(size)
+
+    let children_size = subdirs
+        .par_iter()
+        .map(|(_, dir_path, metadata)| dir_size(dir_path, metadata, walk).merge(DirSize::item()))
+        .chain(
+            symlinks
+                .par_iter()
+                .map(|(_, link_path)| walk.symlink(link_path)),
+        )
+        .reduce(DirSize::default, DirSize::merge);
+
+    if walk.index.is_some() && indexable && !children_size.interrupted {
+        walk.batch.add(
+            path,
+            metadata,
+            files.clone(),
+            subdirs.into_iter().map(|(name, _, _)| name).collect(),
+            symlinks.into_iter().map(|(name, _)| name).collect(),
+        );
+    }
+
+    files.merge(children_size)
+}
+
+/// Hard-linked files are left out of the index: reusing their size would
+/// skip the (device, inode) bookkeeping that counts them once.
+#[cfg(unix)]
+fn is_hard_linked(metadata: &Metadata) -> bool {
+    std::os::unix::fs::MetadataExt::nlink(metadata) > 1
+}
+
+#[cfg(not(unix))]
+fn is_hard_linked(_metadata: &Metadata) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use walkdir::WalkDir;
+
+    /// Three levels of folders with files of different sizes.
+    fn sample_tree(root: &Path) {
+        for a in 0..4 {
+            for b in 0..3 {
+                let dir = root.join(format!("a{a}/b{b}"));
+                std::fs::create_dir_all(&dir).unwrap();
+                for file in 0..5 {
+                    let len = 100 * (a + 1) * (b + 1) + file;
+                    std::fs::write(dir.join(format!("{file}.bin")), vec![0; len]).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_walks_match_a_serial_walk() {
+        let temp = tempfile::tempdir().unwrap();
+        sample_tree(temp.path());
+
+        let entries: Vec<_> = WalkDir::new(temp.path())
+            .min_depth(1)
+            .into_iter()
+            .map(|entry| entry.unwrap())
+            .collect();
+        let files: Vec<_> = entries.iter().filter(|e| e.file_type().is_file()).collect();
+        let bytes: u64 = files.iter().map(|e| e.metadata().unwrap().len()).sum();
+
+        for threads in [1, 2, 8] {
+            set_thread_budget(threads).unwrap();
+            let size = get_dir_size_observed(temp.path(), &NoProgress);
+
+            assert_eq!(size.bytes, bytes, "{threads} threads");
+            assert_eq!(size.file_count, files.len() as u64, "{threads} threads");
+            assert_eq!(size.item_count, entries.len() as u64, "{threads} threads");
+        }
+        set_thread_budget(default_thread_budget()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_folders_make_the_size_a_lower_bound() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let locked = temp.path().join("locked");
+        std::fs::create_dir_all(&locked).unwrap();
+        std::fs::write(locked.join("hidden.bin"), vec![0; 4096]).unwrap();
+        std::fs::write(temp.path().join("seen.bin"), vec![0; 1000]).unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+        // Root reads it anyway, so there is nothing to test.
+        let readable = std::fs::read_dir(&locked).is_ok();
+
+        let size = get_dir_size_observed(temp.path(), &NoProgress);
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+        if readable {
+            return;
+        }
+
+        assert!(size.bytes >= 1000);
+        assert_eq!(size.unreadable_count, 1);
+        assert_eq!(size.unreadable_sample, [locked.to_string_lossy()]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_are_counted_once_and_flagged_when_linked_outside() {
+        use std::os::unix::fs::MetadataExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("root");
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        std::fs::write(root.join("a.bin"), vec![0; 8192]).unwrap();
+        std::fs::hard_link(root.join("a.bin"), root.join("nested/b.bin")).unwrap();
+        let allocated = std::fs::metadata(root.join("a.bin")).unwrap().blocks() * 512;
+
+        let size = get_dir_size_observed(&root, &NoProgress);
+        assert_eq!(size.bytes, 8192);
+        assert_eq!(size.allocated_bytes, allocated);
+        assert_eq!(size.linked_elsewhere_files, 0);
+        assert_eq!(size.reclaimable_bytes(), allocated);
+
+        std::fs::hard_link(root.join("a.bin"), temp.path().join("outside.bin")).unwrap();
+        let size = get_dir_size_observed(&root, &NoProgress);
+        assert_eq!(size.bytes, 8192);
+        assert_eq!(size.linked_elsewhere_files, 1);
+        assert_eq!(size.linked_elsewhere_bytes, allocated);
+        assert_eq!(size.reclaimable_bytes(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_not_followed_and_external_ones_are_reported() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("root");
+        let outside = temp.path().join("outside");
+        std::fs::create_dir_all(root.join("inner")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(root.join("inner/a.bin"), vec![0; 100]).unwrap();
+        std::fs::write(outside.join("big.bin"), vec![0; 10_000]).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("external")).unwrap();
+        std::os::unix::fs::symlink("inner", root.join("internal")).unwrap();
+
+        let size = get_dir_size_observed(&root, &NoProgress);
+        assert_eq!(size.bytes, 100);
+        assert_eq!(size.external_symlink_count, 1);
+        assert_eq!(
+            size.external_symlink_sample,
+            [root.join("external").to_string_lossy()]
+        );
+    }
 }
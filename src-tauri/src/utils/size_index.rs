@@ -0,0 +1,404 @@
+use crate::utils::DirSize;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::UNIX_EPOCH;
+
+/// Stored in the app data dir.
+pub const SIZE_INDEX_FILE: &str = "size_index.db";
+
+static SIZE_INDEX: OnceLock<SizeIndex> = OnceLock::new();
+
+/// What a directory held the last time it was read, valid for as long as
+/// its mtime and inode are unchanged.
+///
+/// Only direct files are summed here; subdirectories are listed by name and
+/// checked on their own, so an unchanged directory costs one `stat` instead
+/// of a `read_dir` plus a `stat` per file. Files rewritten in place without
+/// touching the directory are not noticed until the directory changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedDir {
+    pub modified_ns: u64,
+    pub inode: u64,
+    pub files: DirSize,
+    pub subdirs: Vec<String>,
+    pub symlinks: Vec<String>,
+}
+
+/// Kept sorted so everything below a path is one key range.
+#[derive(Debug, Default)]
+struct IndexData {
+    dirs: BTreeMap<String, IndexedDir>,
+    /// Last complete total of every sized category candidate.
+    roots: BTreeMap<String, DirSize>,
+}
+
+/// Changes not written to the database yet, in the order they were made.
+enum Change {
+    Dirs(Vec<(String, IndexedDir)>),
+    Root(String, DirSize),
+    Forget(String),
+}
+
+pub struct SizeIndex {
+    db: Mutex<Connection>,
+    data: RwLock<IndexData>,
+    pending: Mutex<Vec<Change>>,
+    /// Directory entries were checked for folders deleted since.
+    pruned: AtomicBool,
+}
+
+/// Directory entries gathered by one walk and stored together once it ends,
+/// instead of taking the index lock per directory.
+#[derive(Default)]
+pub struct IndexBatch(Mutex<Vec<(String, IndexedDir)>>);
+
+impl IndexBatch {
+    pub fn add(
+        &self,
+        path: &Path,
+        metadata: &Metadata,
+        files: DirSize,
+        subdirs: Vec<String>,
+        symlinks: Vec<String>,
+    ) {
+        let (modified_ns, inode) = dir_stamp(metadata);
+        let entry = IndexedDir {
+            modified_ns,
+            inode,
+            files,
+            subdirs,
+            symlinks,
+        };
+
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((key(path), entry));
+    }
+}
+
+/// Opens the index in `file`, starting empty when it cannot be read. Only
+/// the first call has any effect. The index can hold a large share of the
+/// disk's folders, so call this off the UI thread; walks run without it
+/// until it is loaded.
+pub fn load_size_index(file: PathBuf) {
+    if SIZE_INDEX.get().is_some() {
+        return;
+    }
+
+    // Written by earlier versions as a single JSON blob.
+    let _ = std::fs::remove_file(file.with_extension("json"));
+
+    let index = SizeIndex::open(&file).unwrap_or_else(|_| SizeIndex::in_memory());
+    let _ = SIZE_INDEX.set(index);
+}
+
+pub fn size_index() -> Option<&'static SizeIndex> {
+    SIZE_INDEX.get()
+}
+
+/// Writes changes made since the last save to the database.
+pub fn save_size_index() -> Result<(), String> {
+    match size_index() {
+        Some(index) => index.save(),
+        None => Ok(()),
+    }
+}
+
+fn dir_stamp(metadata: &Metadata) -> (u64, u64) {
+    let modified_ns = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+
+    #[cfg(unix)]
+    let inode = std::os::unix::fs::MetadataExt::ino(metadata);
+    #[cfg(not(unix))]
+    let inode = 0;
+
+    (modified_ns, inode)
+}
+
+fn key(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// The key range holding everything strictly below `key`.
+fn below(key: &str) -> (String, String) {
+    let key = key.trim_end_matches(MAIN_SEPARATOR);
+    let after_separator = (MAIN_SEPARATOR as u8 + 1) as char;
+    (
+        format!("{key}{MAIN_SEPARATOR}"),
+        format!("{key}{after_separator}"),
+    )
+}
+
+fn remove_tree<V>(map: &mut BTreeMap<String, V>, key: &str) {
+    let (start, end) = below(key);
+    let nested: Vec<String> = map.range(start..end).map(|(key, _)| key.clone()).collect();
+    for key in nested {
+        map.remove(&key);
+    }
+    map.remove(key);
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS dirs (path TEXT PRIMARY KEY, entry TEXT NOT NULL) WITHOUT ROWID;
+    CREATE TABLE IF NOT EXISTS roots (path TEXT PRIMARY KEY, size TEXT NOT NULL) WITHOUT ROWID;
+";
+
+fn read_table<V: for<'de> Deserialize<'de>>(
+    db: &Connection,
+    table: &str,
+) -> rusqlite::Result<BTreeMap<String, V>> {
+    let mut rows = BTreeMap::new();
+    let mut statement = db.prepare(&format!("SELECT * FROM {table}"))?;
+    let mut query = statement.query([])?;
+    while let Some(row) = query.next()? {
+        let value: String = row.get(1)?;
+        // Rows from an older format are dropped and written again later.
+        if let Ok(value) = serde_json::from_str(&value) {
+            rows.insert(row.get(0)?, value);
+        }
+    }
+    Ok(rows)
+}
+
+impl SizeIndex {
+    pub fn open(file: &Path) -> Result<Self, String> {
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+
+        let db = Connection::open(file)
+            .and_then(|db| db.execute_batch(SCHEMA).map(|_| db))
+            .map_err(|e| format!("Failed to open size index: {e}"))?;
+        let data = read_table(&db, "dirs")
+            .and_then(|dirs| {
+                Ok(IndexData {
+                    dirs,
+                    roots: read_table(&db, "roots")?,
+                })
+            })
+            .map_err(|e| format!("Failed to read size index: {e}"))?;
+
+        Ok(SizeIndex::new(db, data))
+    }
+
+    /// Used when the database cannot be opened; nothing survives a restart.
+    fn in_memory() -> Self {
+        let db = Connection::open_in_memory().expect("failed to open an in-memory database");
+        let _ = db.execute_batch(SCHEMA);
+        SizeIndex::new(db, IndexData::default())
+    }
+
+    fn new(db: Connection, data: IndexData) -> Self {
+        SizeIndex {
+            db: Mutex::new(db),
+            data: RwLock::new(data),
+            pending: Mutex::default(),
+            pruned: AtomicBool::new(false),
+        }
+    }
+
+    fn change(&self, change: Change) {
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(change);
+    }
+
+    /// The stored entry for `path`, if the directory has not changed since.
+    pub fn lookup(&self, path: &Path, metadata: &Metadata) -> Option<IndexedDir> {
+        let (modified_ns, inode) = dir_stamp(metadata);
+        let data = self.data.read().unwrap_or_else(|e| e.into_inner());
+
+        data.dirs
+            .get(&key(path))
+            .filter(|entry| entry.modified_ns == modified_ns && entry.inode == inode)
+            .cloned()
+    }
+
+    pub fn store(&self, batch: IndexBatch) {
+        let entries = batch.0.into_inner().unwrap_or_else(|e| e.into_inner());
+        if entries.is_empty() {
+            return;
+        }
+
+        {
+            let mut data = self.data.write().unwrap_or_else(|e| e.into_inner());
+            for (path, entry) in &entries {
+                data.dirs.insert(path.clone(), entry.clone());
+            }
+        }
+        self.change(Change::Dirs(entries));
+    }
+
+    pub fn record_root(&self, path: &Path, size: &DirSize) {
+        self.data
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .roots
+            .insert(key(path), size.clone());
+        self.change(Change::Root(key(path), size.clone()));
+    }
+
+    pub fn root_size(&self, path: &Path) -> Option<DirSize> {
+        self.data
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .roots
+            .get(&key(path))
+            .cloned()
+    }
+
+    /// Drops `path` and everything below it, e.g. after it was deleted.
+    pub fn forget(&self, path: &Path) {
+        let path = key(path);
+        {
+            let mut data = self.data.write().unwrap_or_else(|e| e.into_inner());
+            remove_tree(&mut data.dirs, &path);
+            remove_tree(&mut data.roots, &path);
+        }
+        self.change(Change::Forget(path));
+    }
+
+    /// Writes pending changes in one transaction, first dropping entries
+    /// for folders deleted outside the app: roots on every save,
+    /// directories, which cost a `stat` each, on the first save of a
+    /// session. Readers are only blocked while gone entries are removed.
+    pub fn save(&self) -> Result<(), String> {
+        let prune_dirs = !self.pruned.swap(true, Ordering::Relaxed);
+        let gone: Vec<String> = {
+            let data = self.data.read().unwrap_or_else(|e| e.into_inner());
+            let roots: Vec<String> = data.roots.keys().cloned().collect();
+            let dirs: Vec<String> = match prune_dirs {
+                true => data.dirs.keys().cloned().collect(),
+                false => Vec::new(),
+            };
+            drop(data);
+
+            let gone_roots = roots.into_iter().filter(|root| !Path::new(root).exists());
+            let gone_dirs = dirs.into_iter().filter(|dir| !Path::new(dir).is_dir());
+            gone_roots.chain(gone_dirs).collect()
+        };
+        for path in gone {
+            self.forget(Path::new(&path));
+        }
+
+        // Held across taking the changes so saves write them in order.
+        let mut db = self.db.lock().unwrap_or_else(|e| e.into_inner());
+        let changes = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        write_changes(&mut db, changes).map_err(|e| format!("Failed to save size index: {e}"))
+    }
+}
+
+fn encode(value: &impl Serialize) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn write_changes(db: &mut Connection, changes: Vec<Change>) -> rusqlite::Result<()> {
+    let transaction = db.transaction()?;
+    {
+        let mut store_dir =
+            transaction.prepare("INSERT OR REPLACE INTO dirs (path, entry) VALUES (?1, ?2)")?;
+        let mut store_root =
+            transaction.prepare("INSERT OR REPLACE INTO roots (path, size) VALUES (?1, ?2)")?;
+        let mut forget_dirs = transaction
+            .prepare("DELETE FROM dirs WHERE path = ?1 OR (path >= ?2 AND path < ?3)")?;
+        let mut forget_roots = transaction
+            .prepare("DELETE FROM roots WHERE path = ?1 OR (path >= ?2 AND path < ?3)")?;
+
+        for change in changes {
+            match change {
+                Change::Dirs(entries) => {
+                    for (path, entry) in entries {
+                        store_dir.execute(params![path, encode(&entry)])?;
+                    }
+                }
+                Change::Root(path, size) => {
+                    store_root.execute(params![path, encode(&size)])?;
+                }
+                Change::Forget(path) => {
+                    let (start, end) = below(&path);
+                    forget_dirs.execute(params![path, start, end])?;
+                    forget_roots.execute(params![path, start, end])?;
+                }
+            }
+        }
+    }
+    transaction.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored(index: &SizeIndex, paths: &[&Path]) {
+        let batch = IndexBatch::default();
+        for path in paths {
+            let metadata = std::fs::metadata(path).unwrap();
+            batch.add(path, &metadata, DirSize::default(), Vec::new(), Vec::new());
+            index.record_root(path, &DirSize::default());
+        }
+        index.store(batch);
+    }
+
+    #[test]
+    fn save_drops_entries_for_deleted_folders() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let kept = dir.join("kept");
+        let deleted = dir.join("deleted");
+        std::fs::create_dir_all(&kept).unwrap();
+        std::fs::create_dir_all(&deleted).unwrap();
+
+        let file = dir.join(SIZE_INDEX_FILE);
+        let index = SizeIndex::open(&file).unwrap();
+        stored(&index, &[&kept, &deleted]);
+        std::fs::remove_dir(&deleted).unwrap();
+        index.save().unwrap();
+        drop(index);
+
+        let saved = SizeIndex::open(&file).unwrap().data.into_inner().unwrap();
+        assert_eq!(saved.roots.keys().collect::<Vec<_>>(), [&key(&kept)]);
+        assert_eq!(saved.dirs.keys().collect::<Vec<_>>(), [&key(&kept)]);
+    }
+
+    #[test]
+    fn forget_drops_the_folder_and_everything_below_it() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let cache = dir.join("cache");
+        let nested = cache.join("nested");
+        let sibling = dir.join("cache-old");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir_all(&sibling).unwrap();
+
+        let file = dir.join(SIZE_INDEX_FILE);
+        let index = SizeIndex::open(&file).unwrap();
+        stored(&index, &[&cache, &nested, &sibling]);
+        index.save().unwrap();
+        index.forget(&cache);
+        assert!(index.root_size(&nested).is_none());
+        assert!(index.root_size(&sibling).is_some());
+        index.save().unwrap();
+        drop(index);
+
+        let saved = SizeIndex::open(&file).unwrap().data.into_inner().unwrap();
+        assert_eq!(saved.dirs.keys().collect::<Vec<_>>(), [&key(&sibling)]);
+        assert_eq!(saved.roots.keys().collect::<Vec<_>>(), [&key(&sibling)]);
+    }
+}
@@ -0,0 +1,48 @@
+use std::ffi::{OsStr, OsString};
+use std::sync::{Mutex, MutexGuard};
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Exclusive use of the process environment for one test. Tests that set
+/// variables, or read ones other tests set, hold it so they do not run
+/// into each other; the variables it changed are restored on drop.
+pub struct TestEnv {
+    saved: Vec<(OsString, Option<OsString>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    pub fn lock() -> Self {
+        TestEnv {
+            saved: Vec::new(),
+            _lock: ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner()),
+        }
+    }
+
+    pub fn set(&mut self, var: &str, value: impl AsRef<OsStr>) {
+        self.save(var);
+        std::env::set_var(var, value);
+    }
+
+    pub fn remove(&mut self, var: &str) {
+        self.save(var);
+        std::env::remove_var(var);
+    }
+
+    fn save(&mut self, var: &str) {
+        if !self.saved.iter().any(|(saved, _)| saved == var) {
+            self.saved.push((var.into(), std::env::var_os(var)));
+        }
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        for (var, value) in self.saved.drain(..) {
+            match value {
+                Some(value) => std::env::set_var(var, value),
+                None => std::env::remove_var(var),
+            }
+        }
+    }
+}
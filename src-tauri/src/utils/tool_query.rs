@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Package managers that hang (a network check, a lock) are abandoned.
+const TOOL_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+static TOOL_QUERIES: ToolQueries = ToolQueries::new();
+
+/// Whether tools may be asked, what they answered, and which scan passes
+/// are running. One instance serves the app.
+pub struct ToolQueries {
+    enabled: AtomicBool,
+    answers: OnceLock<Mutex<HashMap<String, Option<PathBuf>>>>,
+    /// Tools are only run while a scan pass is, so listing the categories
+    /// or starting the watcher never waits on a package manager.
+    scans_running: AtomicUsize,
+}
+
+impl ToolQueries {
+    pub const fn new() -> Self {
+        ToolQueries {
+            enabled: AtomicBool::new(true),
+            answers: OnceLock::new(),
+            scans_running: AtomicUsize::new(0),
+        }
+    }
+
+    fn answers(&self) -> &Mutex<HashMap<String, Option<PathBuf>>> {
+        self.answers.get_or_init(Default::default)
+    }
+
+    /// Lets tools run for as long as the scope is held.
+    pub fn scope(&self) -> ToolQueryScope<'_> {
+        self.scans_running.fetch_add(1, Ordering::SeqCst);
+        ToolQueryScope(self)
+    }
+
+    /// What `query`'s tool reports; see [`ToolQuery::answer`].
+    pub fn answer(&self, query: &ToolQuery) -> Option<PathBuf> {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let key = query.command_line();
+        if let Some(answer) = self
+            .answers()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key)
+        {
+            return answer.clone();
+        }
+        if self.scans_running.load(Ordering::SeqCst) == 0 {
+            return None;
+        }
+
+        let answer = run_tool(query.program, query.args).and_then(|output| (query.parse)(&output));
+        self.answers()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, answer.clone());
+        answer
+    }
+}
+
+/// Lets [`ToolQuery::answer`] run tools for as long as it is held. Every
+/// scan pass holds one.
+pub struct ToolQueryScope<'a>(&'a ToolQueries);
+
+impl ToolQueryScope<'static> {
+    pub fn enter() -> Self {
+        TOOL_QUERIES.scope()
+    }
+}
+
+impl Drop for ToolQueryScope<'_> {
+    /// Failures are forgotten once the last pass ends, so a tool installed
+    /// or fixed since is asked again by the next one.
+    fn drop(&mut self) {
+        if self.0.scans_running.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0
+                .answers()
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .retain(|_, answer| answer.is_some());
+        }
+    }
+}
+
+/// Asks a tool where it keeps its cache, e.g. `npm config get cache`.
+#[derive(Debug)]
+pub struct ToolQuery {
+    pub program: &'static str,
+    pub args: &'static [&'static str],
+    pub parse: fn(&str) -> Option<PathBuf>,
+}
+
+impl ToolQuery {
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program)
+            .chain(self.args.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The directory the tool reports, or `None` when queries are disabled or
+    /// the tool is missing, fails or times out. The tool only runs inside a
+    /// [`ToolQueryScope`]; elsewhere its last answer is used. Answers are kept
+    /// for the rest of the session, failures until the scan passes end.
+    pub fn answer(&self) -> Option<PathBuf> {
+        TOOL_QUERIES.answer(self)
+    }
+}
+
+pub fn set_tool_queries_enabled(enabled: bool) {
+    TOOL_QUERIES.enabled.store(enabled, Ordering::Relaxed);
+}
+
+/// Runs `program` if it is on `PATH` and returns its stdout, giving up after
+/// [`TOOL_QUERY_TIMEOUT`].
+pub fn run_tool(program: &str, args: &[&str]) -> Option<String> {
+    let program = which::which(program).ok()?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // Read on a separate thread so a chatty tool cannot fill the pipe and
+    // block while we wait on it.
+    let mut stdout = child.stdout.take()?;
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+
+    let deadline = Instant::now() + TOOL_QUERY_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+
+    let output = reader.join().ok()?.ok()?;
+    status.success().then_some(output)
+}
+
+/// For tools that print a single path: the last non-empty line, if absolute.
+/// Placeholders such as yarn's `undefined` are rejected by the same check.
+pub fn parse_path_output(output: &str) -> Option<PathBuf> {
+    output
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+/// The cache root from `pod cache list`, which lists every cached pod with a
+/// `Pod:` path inside `<root>/Pods/`.
+pub fn parse_pod_cache_list(output: &str) -> Option<PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Pod:"))
+        .map(|path| Path::new(path.trim()))
+        .filter(|path| path.is_absolute())
+        .find_map(|path| {
+            path.ancestors()
+                .find(|ancestor| ancestor.file_name() == Some("Pods".as_ref()))
+                .and_then(Path::parent)
+                .map(Path::to_path_buf)
+        })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tools_only_run_in_a_scan_and_failures_are_asked_again() {
+        let temp = tempfile::tempdir().unwrap();
+        let marker = temp.path().join("cache");
+        // Answers when the marker exists.
+        let script = format!("test -f '{0}' && echo '{0}'", marker.display());
+        let query = ToolQuery {
+            program: "sh",
+            args: Box::leak(Box::new(["-c", script.leak()])),
+            parse: parse_path_output,
+        };
+        let queries = ToolQueries::new();
+
+        std::fs::write(&marker, "").unwrap();
+        assert_eq!(queries.answer(&query), None, "ran outside a scan");
+
+        std::fs::remove_file(&marker).unwrap();
+        let scope = queries.scope();
+        assert_eq!(queries.answer(&query), None);
+        drop(scope);
+
+        std::fs::write(&marker, "").unwrap();
+        let _scope = queries.scope();
+        assert_eq!(queries.answer(&query), Some(marker.clone()));
+    }
+}
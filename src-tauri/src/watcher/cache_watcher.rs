@@ -0,0 +1,463 @@
+use crate::models::{RiskLevel, ScanResult, SizesChanged};
+use crate::registry::{CacheTarget, CandidatePath, CACHE_TARGETS};
+use crate::tray::set_tray_reclaimable;
+use crate::utils::{
+    get_candidate_size, save_size_index, size_index, DirSize, NoProgress, SizeIndex,
+};
+use notify::{Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+pub const SIZES_CHANGED_EVENT: &str = "sizes_changed";
+
+/// Bursts of writes (a Gradle build, a Metro bundle) are folded into one
+/// re-measure per root.
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// How often folders the OS could not watch are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(300);
+
+static REFRESH: OnceLock<mpsc::Sender<notify::Result<Event>>> = OnceLock::new();
+
+struct WatchedRoot {
+    path: PathBuf,
+    source: String,
+    target: &'static dyn CacheTarget,
+    size: ScanResult,
+    reclaimable: u64,
+}
+
+impl WatchedRoot {
+    fn new(candidate: CandidatePath, target: &'static dyn CacheTarget, size: DirSize) -> Self {
+        let mut root = WatchedRoot {
+            path: candidate.path,
+            source: candidate.source,
+            target,
+            size: ScanResult::default(),
+            reclaimable: 0,
+        };
+        root.update(size);
+        root
+    }
+
+    fn remeasure(&mut self) {
+        self.update(get_candidate_size(&self.path, &NoProgress));
+    }
+
+    fn update(&mut self, size: DirSize) {
+        self.reclaimable = size.reclaimable_bytes();
+        self.size = ScanResult {
+            path_source: self.source.clone(),
+            ..ScanResult::from_dir_size(&self.path, self.target.id(), self.target.owner(), size)
+        };
+    }
+}
+
+/// Watches the cache roots in the background and pushes a `sizes_changed`
+/// event, and a new tray tooltip, when their sizes move.
+///
+/// Only roots a scan has sized are followed, starting from the sizes in the
+/// index, so nothing is walked up front. Their folders and parents are
+/// watched without recursing, which keeps the watch count to a few per
+/// root: a new entry in a root re-measures it through the index, a folder
+/// appearing next to one resolves the roots again.
+///
+/// High-risk categories are skipped: they cover broad trees such as `/tmp`
+/// that are not useful to track live.
+pub fn start_cache_watcher(app: AppHandle) {
+    let Some(index) = size_index() else {
+        return;
+    };
+
+    std::thread::spawn(move || {
+        let (tx, rx) = mpsc::channel();
+        let _ = REFRESH.set(tx.clone());
+        let targets = CACHE_TARGETS
+            .iter()
+            .copied()
+            .filter(|target| target.risk() != RiskLevel::High)
+            .collect();
+        let mut watcher = CacheWatcher::new(tx, targets, index);
+
+        let changed = watcher.resolve();
+        publish(&app, &watcher, changed);
+
+        while let Some(events) = next_events(&rx) {
+            let changed = watcher.apply(events);
+            if changed.is_empty() {
+                continue;
+            }
+
+            let _ = save_size_index();
+            publish(&app, &watcher, changed);
+        }
+    });
+}
+
+/// Has the watcher resolve its roots again, e.g. after a scan sized new
+/// ones.
+pub fn refresh_cache_watcher() {
+    if let Some(tx) = REFRESH.get() {
+        let _ = tx.send(Ok(Event::new(EventKind::Other)));
+    }
+}
+
+struct CacheWatcher {
+    tx: mpsc::Sender<notify::Result<Event>>,
+    /// Dropped once the OS runs out of watches; everything is polled then.
+    native: Option<RecommendedWatcher>,
+    poll: Option<PollWatcher>,
+    /// Folders watched natively and by polling, none of them recursively.
+    watched: Vec<PathBuf>,
+    polled: Vec<PathBuf>,
+    targets: Vec<&'static dyn CacheTarget>,
+    index: &'static SizeIndex,
+    roots: Vec<WatchedRoot>,
+}
+
+impl CacheWatcher {
+    fn new(
+        tx: mpsc::Sender<notify::Result<Event>>,
+        targets: Vec<&'static dyn CacheTarget>,
+        index: &'static SizeIndex,
+    ) -> Self {
+        CacheWatcher {
+            native: notify::recommended_watcher(tx.clone()).ok(),
+            tx,
+            poll: None,
+            watched: Vec::new(),
+            polled: Vec::new(),
+            targets,
+            index,
+            roots: Vec::new(),
+        }
+    }
+
+    /// Resolves every category's candidates and watch roots again. Indexed
+    /// candidates not followed yet are added with their indexed size,
+    /// vanished ones reported at zero and dropped, and folders not watched
+    /// yet are watched. Returns what changed.
+    fn resolve(&mut self) -> Vec<ScanResult> {
+        let mut candidates = Vec::new();
+        let mut folders = Vec::new();
+        for target in &self.targets {
+            for candidate in target.candidates().unwrap_or_default() {
+                folders.extend(candidate.path.parent().map(Path::to_path_buf));
+                if candidate.path.is_dir() {
+                    candidates.push((candidate, *target));
+                }
+            }
+            folders.extend(target.watch_roots().into_iter().map(|root| root.path));
+        }
+
+        let is_root = |root: &WatchedRoot, path: &Path, target: &dyn CacheTarget| {
+            root.path == path && root.target.id() == target.id()
+        };
+        let mut changed = Vec::new();
+        let (kept, gone): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.roots)
+                .into_iter()
+                .partition(|root| {
+                    candidates
+                        .iter()
+                        .any(|(candidate, target)| is_root(root, &candidate.path, *target))
+                });
+        for mut root in gone {
+            root.update(DirSize::default());
+            changed.push(root.size);
+        }
+        self.roots = kept;
+
+        for (candidate, target) in candidates {
+            if self
+                .roots
+                .iter()
+                .any(|root| is_root(root, &candidate.path, target))
+            {
+                continue;
+            }
+            // Roots no scan has sized yet are left for one to find.
+            let Some(size) = self.index.root_size(&candidate.path) else {
+                continue;
+            };
+
+            folders.push(candidate.path.clone());
+            let root = WatchedRoot::new(candidate, target, size);
+            changed.push(root.size.clone());
+            self.roots.push(root);
+        }
+
+        self.watched.retain(|dir| dir.is_dir());
+        self.polled.retain(|dir| dir.is_dir());
+        folders.extend(self.roots.iter().map(|root| root.path.clone()));
+        folders.retain(|dir| dir.is_dir());
+        folders.sort();
+        folders.dedup();
+        for dir in folders {
+            if !self.watched.contains(&dir) && !self.polled.contains(&dir) {
+                self.watch(dir);
+            }
+        }
+
+        changed
+    }
+
+    /// Watches `dir` natively, falling back to polling. A native failure is
+    /// usually the OS running out of watches (`fs.inotify.max_user_watches`
+    /// on Linux), taken by other tools.
+    fn watch(&mut self, dir: PathBuf) {
+        if let Some(native) = &mut self.native {
+            match native.watch(&dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    self.watched.push(dir);
+                    return;
+                }
+                Err(e) if matches!(e.kind, notify::ErrorKind::MaxFilesWatch) => {
+                    self.fall_back_to_polling();
+                }
+                Err(_) => {
+                    let _ = native.unwatch(&dir);
+                }
+            }
+        }
+
+        self.poll(dir);
+    }
+
+    /// Drops the native watcher, releasing all of its watches, and polls
+    /// every folder it covered instead.
+    fn fall_back_to_polling(&mut self) {
+        self.native = None;
+        for dir in std::mem::take(&mut self.watched) {
+            self.poll(dir);
+        }
+    }
+
+    /// Polls `dir`'s own entries, starting the poll watcher on first use.
+    fn poll(&mut self, dir: PathBuf) {
+        if self.poll.is_none() {
+            let config = notify::Config::default().with_poll_interval(POLL_INTERVAL);
+            self.poll = PollWatcher::new(self.tx.clone(), config).ok();
+        }
+
+        let Some(poll) = &mut self.poll else {
+            return;
+        };
+        if poll.watch(&dir, RecursiveMode::NonRecursive).is_ok() {
+            self.polled.push(dir);
+        }
+    }
+
+    /// Remeasures the roots `events` touched. Roots are resolved again when
+    /// a folder appears or goes outside them, which may be a new root or a
+    /// vanished one, and on a [`refresh_cache_watcher`] request.
+    fn apply(&mut self, events: Vec<notify::Result<Event>>) -> Vec<ScanResult> {
+        let mut dirty = HashSet::new();
+        let mut rescan = false;
+
+        for event in events {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) if matches!(e.kind, notify::ErrorKind::MaxFilesWatch) => {
+                    self.fall_back_to_polling();
+                    continue;
+                }
+                Err(_) => continue,
+            };
+            rescan |= event.paths.is_empty();
+
+            let comes_or_goes = event.kind.is_create()
+                || event.kind.is_remove()
+                || matches!(
+                    event.kind,
+                    EventKind::Modify(notify::event::ModifyKind::Name(_))
+                );
+            for path in &event.paths {
+                let touched: Vec<usize> = self
+                    .roots
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, root)| path.starts_with(&root.path))
+                    .map(|(i, _)| i)
+                    .collect();
+                let lost_root = self.roots.iter().any(|root| root.path == *path);
+                rescan |= comes_or_goes
+                    && (lost_root || (touched.is_empty() && (path.is_dir() || !path.exists())));
+                dirty.extend(touched);
+            }
+        }
+
+        let mut changed: Vec<ScanResult> = self
+            .roots
+            .iter_mut()
+            .enumerate()
+            .filter(|(i, root)| dirty.contains(i) && root.path.is_dir())
+            .map(|(_, root)| {
+                root.remeasure();
+                root.size.clone()
+            })
+            .collect();
+
+        if rescan {
+            changed.extend(self.resolve());
+        }
+        changed
+    }
+}
+
+/// Blocks for the next change, then gathers events until things settle.
+/// Returns `None` once the watchers are gone.
+fn next_events(rx: &mpsc::Receiver<notify::Result<Event>>) -> Option<Vec<notify::Result<Event>>> {
+    let first = rx.recv().ok()?;
+
+    let mut events = vec![first];
+    let settled_at = Instant::now() + DEBOUNCE;
+    while let Ok(event) = rx.recv_timeout(settled_at.saturating_duration_since(Instant::now())) {
+        events.push(event);
+    }
+
+    Some(events)
+}
+
+fn publish(app: &AppHandle, watcher: &CacheWatcher, changed: Vec<ScanResult>) {
+    let total_reclaimable = watcher.roots.iter().map(|root| root.reclaimable).sum();
+
+    set_tray_reclaimable(app, total_reclaimable);
+    let _ = app.emit(
+        SIZES_CHANGED_EVENT,
+        SizesChanged {
+            changed,
+            total_reclaimable,
+            polled: watcher
+                .polled
+                .iter()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .collect(),
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{CacheDefinition, Location, Platform};
+    use notify::event::{CreateKind, RemoveKind};
+
+    fn leak<T>(value: T) -> &'static T {
+        Box::leak(Box::new(value))
+    }
+
+    /// A category with one location per folder in `dir`, and an index in it.
+    fn watcher_over(dir: &Path, folders: &[&str]) -> CacheWatcher {
+        let locations: Vec<Location> = folders
+            .iter()
+            .map(|folder| {
+                let path = dir.join(folder).to_string_lossy().to_string();
+                Location::absolute(Platform::Any, Box::leak(path.into_boxed_str()))
+            })
+            .collect();
+        let target: &'static dyn CacheTarget = leak(CacheDefinition {
+            id: "test_cache",
+            name: "Test cache",
+            description: "",
+            risk: RiskLevel::Low,
+            owner: "test",
+            locations: Box::leak(locations.into_boxed_slice()),
+        });
+        let index = leak(SizeIndex::open(&dir.join("index.db")).unwrap());
+
+        CacheWatcher::new(mpsc::channel().0, vec![target], index)
+    }
+
+    fn indexed_size(bytes: u64) -> DirSize {
+        DirSize {
+            bytes,
+            allocated_bytes: bytes,
+            ..Default::default()
+        }
+    }
+
+    fn event(kind: EventKind, path: &Path) -> notify::Result<Event> {
+        Ok(Event::new(kind).add_path(path.to_path_buf()))
+    }
+
+    #[test]
+    fn resolve_starts_from_indexed_sizes_and_watches_without_recursing() {
+        let temp = tempfile::Builder::new()
+            .prefix("watcher")
+            .tempdir()
+            .unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("sized/nested")).unwrap();
+        std::fs::create_dir_all(dir.join("unsized")).unwrap();
+        let mut watcher = watcher_over(dir, &["sized", "unsized"]);
+        watcher
+            .index
+            .record_root(&dir.join("sized"), &indexed_size(12345));
+
+        let changed = watcher.resolve();
+
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].path, dir.join("sized").to_string_lossy());
+        assert_eq!(changed[0].size, 12345);
+        let mut watched: Vec<PathBuf> = watcher
+            .watched
+            .iter()
+            .chain(&watcher.polled)
+            .cloned()
+            .collect();
+        watched.sort();
+        // Watch roots are followed before a scan sizes them.
+        assert_eq!(
+            watched,
+            [dir.to_path_buf(), dir.join("sized"), dir.join("unsized")]
+        );
+    }
+
+    #[test]
+    fn apply_remeasures_touched_roots_and_resolves_when_folders_come_and_go() {
+        let temp = tempfile::Builder::new()
+            .prefix("watcher")
+            .tempdir()
+            .unwrap();
+        let dir = temp.path();
+        let sized = dir.join("sized");
+        let later = dir.join("later");
+        std::fs::create_dir_all(&sized).unwrap();
+        let mut watcher = watcher_over(dir, &["sized", "later"]);
+        watcher.index.record_root(&sized, &indexed_size(1));
+        watcher.resolve();
+
+        std::fs::write(sized.join("entry.bin"), vec![0; 2048]).unwrap();
+        let changed = watcher.apply(vec![event(
+            EventKind::Create(CreateKind::File),
+            &sized.join("entry.bin"),
+        )]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].size, 2048);
+
+        // A file next to the roots is no reason to resolve them again.
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        let changed = watcher.apply(vec![event(
+            EventKind::Create(CreateKind::File),
+            &dir.join("notes.txt"),
+        )]);
+        assert!(changed.is_empty());
+
+        std::fs::create_dir_all(&later).unwrap();
+        watcher.index.record_root(&later, &indexed_size(64));
+        let changed = watcher.apply(vec![event(EventKind::Create(CreateKind::Folder), &later)]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].size, 64);
+
+        std::fs::remove_dir_all(&sized).unwrap();
+        let changed = watcher.apply(vec![event(EventKind::Remove(RemoveKind::Folder), &sized)]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].path, sized.to_string_lossy());
+        assert_eq!(changed[0].size, 0);
+        assert_eq!(watcher.roots.len(), 1);
+    }
+}
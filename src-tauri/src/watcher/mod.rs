@@ -0,0 +1,3 @@
+pub mod cache_watcher;
+
+pub use cache_watcher::*;
@@ -29,7 +29,9 @@ describe('TauriService', () => {
 
       const result = await TauriService.scanExpoCache()
 
-      expect(mockInvoke).toHaveBeenCalledWith('scan_expo_cache')
+      expect(mockInvoke).toHaveBeenCalledWith('scan_category', {
+        id: 'expo_cache',
+      })
       expect(result).toEqual(mockScanResult)
     })
 
@@ -38,7 +40,9 @@ describe('TauriService', () => {
 
       const result = await TauriService.scanMetroCache()
 
-      expect(mockInvoke).toHaveBeenCalledWith('scan_metro_cache')
+      expect(mockInvoke).toHaveBeenCalledWith('scan_category', {
+        id: 'metro_cache',
+      })
       expect(result).toEqual(mockScanResult)
     })
 
@@ -47,7 +51,9 @@ describe('TauriService', () => {
 
       const result = await TauriService.scanIosCache()
 
-      expect(mockInvoke).toHaveBeenCalledWith('scan_ios_cache')
+      expect(mockInvoke).toHaveBeenCalledWith('scan_category', {
+        id: 'ios_cache',
+      })
       expect(result).toEqual(mockScanResult)
     })
 
@@ -56,7 +62,9 @@ describe('TauriService', () => {
 
       const result = await TauriService.scanAndroidCache()
 
-      expect(mockInvoke).toHaveBeenCalledWith('scan_android_cache')
+      expect(mockInvoke).toHaveBeenCalledWith('scan_category', {
+        id: 'android_cache',
+      })
       expect(result).toEqual(mockScanResult)
     })
 
@@ -65,7 +73,9 @@ describe('TauriService', () => {
 
       const result = await TauriService.scanNpmCache()
 
-      expect(mockInvoke).toHaveBeenCalledWith('scan_npm_cache')
+      expect(mockInvoke).toHaveBeenCalledWith('scan_category', {
+        id: 'npm_cache',
+      })
       expect(result).toEqual(mockScanResult)
     })
 
@@ -74,7 +84,9 @@ describe('TauriService', () => {
 
       const result = await TauriService.scanWatchmanCache()
 
-      expect(mockInvoke).toHaveBeenCalledWith('scan_watchman_cache')
+      expect(mockInvoke).toHaveBeenCalledWith('scan_category', {
+        id: 'watchman_cache',
+      })
       expect(result).toEqual(mockScanResult)
     })
 
@@ -83,7 +95,9 @@ describe('TauriService', () => {
 
       const result = await TauriService.scanCocoaPodsCache()
 
-      expect(mockInvoke).toHaveBeenCalledWith('scan_cocoapods_cache')
+      expect(mockInvoke).toHaveBeenCalledWith('scan_category', {
+        id: 'cocoapods_cache',
+      })
       expect(result).toEqual(mockScanResult)
     })
 
@@ -92,7 +106,9 @@ describe('TauriService', () => {
 
       const result = await TauriService.scanFlipperLogs()
 
-      expect(mockInvoke).toHaveBeenCalledWith('scan_flipper_logs')
+      expect(mockInvoke).toHaveBeenCalledWith('scan_category', {
+        id: 'flipper_logs',
+      })
       expect(result).toEqual(mockScanResult)
     })
 
@@ -101,7 +117,9 @@ describe('TauriService', () => {
 
       const result = await TauriService.scanTempFiles()
 
-      expect(mockInvoke).toHaveBeenCalledWith('scan_temp_files')
+      expect(mockInvoke).toHaveBeenCalledWith('scan_category', {
+        id: 'temp_files',
+      })
       expect(result).toEqual(mockScanResult)
     })
   })
@@ -233,7 +251,9 @@ describe('TauriService', () => {
       await expect(TauriService.scanExpoCache()).rejects.toThrow(
         'Tauri command failed'
       )
-      expect(mockInvoke).toHaveBeenCalledWith('scan_expo_cache')
+      expect(mockInvoke).toHaveBeenCalledWith('scan_category', {
+        id: 'expo_cache',
+      })
     })
 
     test('should handle cleaning errors gracefully', async () => {
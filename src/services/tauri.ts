@@ -5,6 +5,64 @@ export interface ScanResult {
   size: number
   file_type: string
   can_delete: boolean
+  allocated_size?: number
+  linked_elsewhere_size?: number
+  linked_elsewhere_files?: number
+  unreadable_entries?: number
+  unreadable_sample?: string[]
+  external_symlinks?: number
+  external_symlink_sample?: string[]
+  partial?: boolean
+  modified_at?: number | null
+  accessed_at?: number | null
+  newest_file_at?: number | null
+  file_count?: number
+  item_count?: number
+  owner_tool?: string
+  path_source?: string
+  component?: string
+  version?: string | null
+  build?: string | null
+  used_by?: string[]
+  running_owners?: string[]
+  origin?: string | null
+  origin_missing?: boolean
+  last_used_at?: number | null
+  retained?: boolean
+}
+
+export interface ScanProgress {
+  scan_id: string
+  bytes_counted: number
+  current_path: string | null
+  categories_completed: number
+  categories_total: number | null
+  percent: number | null
+}
+
+export interface ScanItemFound {
+  scan_id: string
+  item: ScanResult
+}
+
+export interface ScanFinished {
+  scan_id: string
+  results: ScanResult[]
+  partial: boolean
+  error: string | null
+}
+
+export interface OperationFinished<T> {
+  scan_id: string
+  result: T | null
+  partial: boolean
+  error: string | null
+}
+
+export interface SizesChanged {
+  changed: ScanResult[]
+  total_reclaimable: number
+  polled: string[]
 }
 
 export interface CleaningResult {
@@ -12,6 +70,101 @@ export interface CleaningResult {
   space_freed: number
   duration: number
   errors: string[]
+  deleted_paths?: string[]
+  partial?: boolean
+  stopped_daemons?: string[]
+}
+
+export interface CleanFinished {
+  operation_id: string
+  result: CleaningResult
+}
+
+export type RiskLevel = 'safe' | 'low' | 'medium' | 'high'
+
+export interface CacheCategory {
+  id: string
+  name: string
+  description: string
+  risk: RiskLevel
+  owner: string
+  locations: string[]
+}
+
+export type ProjectKind = 'react_native' | 'expo'
+
+export interface RnProject {
+  path: string
+  name: string
+  kind: ProjectKind
+  markers: string[]
+  artifacts: ScanResult[]
+  artifacts_size: number
+}
+
+export interface WorkspaceConfig {
+  roots: string[]
+  skip_dirs: string[]
+  stale_after_days: number
+  stale_min_bytes: number
+  derived_data_keep: number
+  device_support_keep: number
+  tool_queries: boolean
+}
+
+export interface ProjectActivity {
+  newest_source_at: number | null
+  last_commit_at: number | null
+  last_active_at: number | null
+}
+
+export interface ProjectStaleness {
+  project_path: string
+  node_modules: ScanResult
+  activity: ProjectActivity
+  inactive_days: number | null
+  stale: boolean
+}
+
+export interface DuplicatedPackage {
+  name: string
+  version: string
+  copies: number
+  total_size: number
+  wasted_size: number
+  paths: string[]
+}
+
+export interface DuplicationReport {
+  node_modules_scanned: number
+  packages_scanned: number
+  duplicates: DuplicatedPackage[]
+  total_size: number
+  unique_size: number
+  store_savings: number
+}
+
+export interface RunningDaemon {
+  id: string
+  name: string
+  pid: number
+  command: string
+  categories: string[]
+}
+
+export interface AndroidVirtualDevice {
+  id: string
+  name: string
+  ini_path: string
+  api_level: string | null
+  system_image: string | null
+  device: string | null
+  disk_size: number | null
+  avd: ScanResult
+  snapshots: ScanResult[]
+  user_data: ScanResult[]
+  last_used_at: number | null
+  running: boolean
 }
 
 export interface SystemInfo {
@@ -20,40 +173,96 @@ export interface SystemInfo {
 }
 
 export class TauriService {
+  static async listCategories(): Promise<CacheCategory[]> {
+    return await invoke('list_categories')
+  }
+
+  static async scanCategory(id: string): Promise<ScanResult[]> {
+    return await invoke('scan_category', { id })
+  }
+
+  static async scanAllCategories(): Promise<ScanResult[]> {
+    return await invoke('scan_all_categories')
+  }
+
+  static async getCachedSizes(): Promise<ScanResult[]> {
+    return await invoke('get_cached_sizes')
+  }
+
+  static async startScan(categories?: string[]): Promise<string> {
+    return await invoke('start_scan', { categories })
+  }
+
+  static async startNodeModulesScan(projectPath: string): Promise<string> {
+    return await invoke('start_node_modules_scan', { projectPath })
+  }
+
+  static async setScanThreads(threads: number): Promise<void> {
+    return await invoke('set_scan_threads', { threads })
+  }
+
+  static async getWorkspaceConfig(): Promise<WorkspaceConfig> {
+    return await invoke('get_workspace_config')
+  }
+
+  static async setWorkspaceConfig(config: WorkspaceConfig): Promise<void> {
+    return await invoke('set_workspace_config', { config })
+  }
+
+  static async startProjectScan(): Promise<string> {
+    return await invoke('start_project_scan')
+  }
+
+  static async startStaleProjectsScan(): Promise<string> {
+    return await invoke('start_stale_projects_scan')
+  }
+
+  static async startDuplicationAnalysis(): Promise<string> {
+    return await invoke('start_duplication_analysis')
+  }
+
+  static async setPackageManagerQueries(enabled: boolean): Promise<void> {
+    return await invoke('set_package_manager_queries', { enabled })
+  }
+
   static async scanExpoCache(): Promise<ScanResult[]> {
-    return await invoke('scan_expo_cache')
+    return await TauriService.scanCategory('expo_cache')
   }
 
   static async scanMetroCache(): Promise<ScanResult[]> {
-    return await invoke('scan_metro_cache')
+    return await TauriService.scanCategory('metro_cache')
   }
 
   static async scanIosCache(): Promise<ScanResult[]> {
-    return await invoke('scan_ios_cache')
+    return await TauriService.scanCategory('ios_cache')
   }
 
   static async scanAndroidCache(): Promise<ScanResult[]> {
-    return await invoke('scan_android_cache')
+    return await TauriService.scanCategory('android_cache')
+  }
+
+  static async scanAndroidSdk(): Promise<ScanResult[]> {
+    return await TauriService.scanCategory('android_sdk')
   }
 
   static async scanNpmCache(): Promise<ScanResult[]> {
-    return await invoke('scan_npm_cache')
+    return await TauriService.scanCategory('npm_cache')
   }
 
   static async scanWatchmanCache(): Promise<ScanResult[]> {
-    return await invoke('scan_watchman_cache')
+    return await TauriService.scanCategory('watchman_cache')
   }
 
   static async scanCocoaPodsCache(): Promise<ScanResult[]> {
-    return await invoke('scan_cocoapods_cache')
+    return await TauriService.scanCategory('cocoapods_cache')
   }
 
   static async scanFlipperLogs(): Promise<ScanResult[]> {
-    return await invoke('scan_flipper_logs')
+    return await TauriService.scanCategory('flipper_logs')
   }
 
   static async scanTempFiles(): Promise<ScanResult[]> {
-    return await invoke('scan_temp_files')
+    return await TauriService.scanCategory('temp_files')
   }
 
   static async scanDockerContainers(): Promise<ScanResult[]> {
@@ -82,8 +291,28 @@ export class TauriService {
     return await invoke('scan_node_modules', { projectPath })
   }
 
-  static async cleanFiles(filePaths: string[]): Promise<CleaningResult> {
-    return await invoke('clean_files', { filePaths })
+  static async cleanFiles(filePaths: string[], stopDaemons?: boolean): Promise<CleaningResult> {
+    return await invoke('clean_files', { filePaths, stopDaemons })
+  }
+
+  static async startClean(filePaths: string[], stopDaemons?: boolean): Promise<string> {
+    return await invoke('start_clean', { filePaths, stopDaemons })
+  }
+
+  static async listRunningDaemons(): Promise<RunningDaemon[]> {
+    return await invoke('list_running_daemons')
+  }
+
+  static async scanAndroidVirtualDevices(): Promise<AndroidVirtualDevice[]> {
+    return await invoke('scan_android_virtual_devices')
+  }
+
+  static async deleteAndroidVirtualDevice(id: string): Promise<CleaningResult> {
+    return await invoke('delete_android_virtual_device', { id })
+  }
+
+  static async cancelOperation(id: string): Promise<void> {
+    return await invoke('cancel_operation', { id })
   }
 
   static async getSystemInfo(): Promise<SystemInfo> {
@@ -95,27 +324,27 @@ export class TauriService {
   }
 
   static async scanReactNativeCache(): Promise<ScanResult[]> {
-    return await invoke('scan_react_native_cache')
+    return await TauriService.scanCategory('react_native_cache')
   }
 
   static async scanHermesCache(): Promise<ScanResult[]> {
-    return await invoke('scan_hermes_cache')
+    return await TauriService.scanCategory('hermes_cache')
   }
 
   static async scanVsCodeCache(): Promise<ScanResult[]> {
-    return await invoke('scan_vscode_cache')
+    return await TauriService.scanCategory('vscode_cache')
   }
 
   static async scanAndroidStudioCache(): Promise<ScanResult[]> {
-    return await invoke('scan_android_studio_cache')
+    return await TauriService.scanCategory('android_studio_cache')
   }
 
   static async scanBuildArtifacts(): Promise<ScanResult[]> {
-    return await invoke('scan_build_artifacts')
+    return await TauriService.scanCategory('build_artifacts')
   }
 
   static async scanHomebrewCache(): Promise<ScanResult[]> {
-    return await invoke('scan_homebrew_cache')
+    return await TauriService.scanCategory('homebrew_cache')
   }
 }
 